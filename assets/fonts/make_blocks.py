@@ -0,0 +1,120 @@
+#!/usr/bin/env python3
+"""Generate FurBlocks.ttf, the font used by the tests and examples of Fur.
+
+Every printable ASCII character except the space is the same filled box, so the pixels
+covered by a string are easy to predict. The font is 1000 units per em, every advance is
+500 units, and the box covers x 50..450 and y 0..700.
+"""
+import struct
+import sys
+
+UNITS_PER_EM = 1000
+ADVANCE = 500
+ASCENDER, DESCENDER = 800, -200
+BOX = (50, 0, 450, 700)
+FAMILY = "Fur Blocks"
+
+
+def box_glyph():
+    x_min, y_min, x_max, y_max = BOX
+    points = [(x_min, y_min), (x_min, y_max), (x_max, y_max), (x_max, y_min)]
+    data = struct.pack(">hhhhh", 1, x_min, y_min, x_max, y_max)
+    data += struct.pack(">HH", len(points) - 1, 0)
+    data += bytes([0x01] * len(points))
+    previous = (0, 0)
+    x_deltas, y_deltas = b"", b""
+    for x, y in points:
+        x_deltas += struct.pack(">h", x - previous[0])
+        y_deltas += struct.pack(">h", y - previous[1])
+        previous = (x, y)
+    data += x_deltas + y_deltas
+    return data + b"\0" * (-len(data) % 4)
+
+
+def tables():
+    # Glyph 0 is .notdef, 1 is the space and 2 is the box.
+    glyphs = [b"", b"", box_glyph()]
+    glyf = b"".join(glyphs)
+    offsets = [0]
+    for glyph in glyphs:
+        offsets.append(offsets[-1] + len(glyph))
+    loca = b"".join(struct.pack(">H", offset // 2) for offset in offsets)
+    x_min, y_min, x_max, y_max = BOX
+
+    head = struct.pack(
+        ">HHiIIHHqqhhhhHHhhh",
+        1, 0, 0x10000, 0, 0x5F0F3CF5, 0x000B, UNITS_PER_EM, 0, 0,
+        x_min, y_min, x_max, y_max, 0, 8, 2, 0, 0,
+    )
+    hhea = struct.pack(
+        ">HHhhhHhhhhhhhhhhhH",
+        1, 0, ASCENDER, DESCENDER, 0, ADVANCE, 0, 0, x_max,
+        1, 0, 0, 0, 0, 0, 0, 0, len(glyphs),
+    )
+    maxp = struct.pack(">IHHHHHHHHHHHHHH", 0x10000, len(glyphs), 4, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0)
+    hmtx = b"".join(struct.pack(">Hh", ADVANCE, x_min if glyph else 0) for glyph in glyphs)
+    os2 = struct.pack(
+        ">HhHHHhhhhhhhhhhh10sIIII4sHHHhhhHHIIhhHHH",
+        4, ADVANCE, 400, 5, 0,
+        650, 700, 0, 140, 650, 700, 0, 480, 50, 250, 0,
+        bytes(10), 1, 0, 0, 0, b"NONE", 0x40, 0x20, 0x7E,
+        ASCENDER, DESCENDER, 0, ASCENDER, -DESCENDER, 1, 0,
+        500, y_max, 0, 0x20, 1,
+    )
+    # A format 4 subtable mapping the space to glyph 1, and 0x21..=0x7E to glyph 2 through the glyph id array.
+    segments = [(0x20, 0x20, 1 - 0x20), (0x21, 0x7E, 0), (0xFFFF, 0xFFFF, 1)]
+    count = len(segments)
+    range_offsets = [0, 2 * (count - 1), 0]
+    glyph_ids = [2] * (0x7E - 0x21 + 1)
+    subtable = struct.pack(">HHHH", count * 2, 4, 1, count * 2 - 4)
+    subtable += b"".join(struct.pack(">H", end) for _, end, _ in segments) + b"\0\0"
+    subtable += b"".join(struct.pack(">H", start) for start, _, _ in segments)
+    subtable += b"".join(struct.pack(">h", delta) for _, _, delta in segments)
+    subtable += b"".join(struct.pack(">H", offset) for offset in range_offsets)
+    subtable += b"".join(struct.pack(">H", glyph_id) for glyph_id in glyph_ids)
+    subtable = struct.pack(">HHH", 4, 6 + len(subtable), 0) + subtable
+    cmap = struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+    names = {1: FAMILY, 2: "Regular", 4: FAMILY, 6: FAMILY.replace(" ", "")}
+    strings = b""
+    records = b""
+    for name_id, text in names.items():
+        encoded = text.encode("utf-16-be")
+        records += struct.pack(">HHHHHH", 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    name = struct.pack(">HHH", 0, len(names), 6 + len(records)) + records + strings
+    post = struct.pack(">IihhIIIII", 0x30000, 0, -100, 50, 0, 0, 0, 0, 0)
+
+    return {
+        b"OS/2": os2, b"cmap": cmap, b"glyf": glyf, b"head": head, b"hhea": hhea,
+        b"hmtx": hmtx, b"loca": loca, b"maxp": maxp, b"name": name, b"post": post,
+    }
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def build():
+    tables_ = tables()
+    count = len(tables_)
+    search = 1 << (count.bit_length() - 1)
+    header = struct.pack(">IHHHH", 0x10000, count, search * 16, search.bit_length() - 1, count * 16 - search * 16)
+    offset = len(header) + count * 16
+    directory, body = b"", b""
+    for tag in sorted(tables_):
+        data = tables_[tag]
+        directory += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+    font = bytearray(header + directory + body)
+    head_offset = struct.unpack(">I", font[font.index(b"head") + 8:font.index(b"head") + 12])[0]
+    adjustment = (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF
+    font[head_offset + 8:head_offset + 12] = struct.pack(">I", adjustment)
+    return bytes(font)
+
+
+if __name__ == "__main__":
+    path = sys.argv[1] if len(sys.argv) > 1 else "FurBlocks.ttf"
+    with open(path, "wb") as file:
+        file.write(build())
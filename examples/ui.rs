@@ -67,17 +67,15 @@ fn main() {
     let window_layer_mut = display.layer_mut(&window_layer).unwrap();
     fur::window::WindowBuilder::new(100, 50).draw(window_layer_mut);
 
-    let color = Color::new_argb(0, 0x11, 0x45, 0x14);
+    let color = Color::new_rgb(0x11, 0x45, 0x14);
     let background_layer = display.create_layer(WIDTH, HEIGHT, 0, 0);
     display
         .layer_mut(&background_layer)
         .unwrap()
         .write(0, 0, WIDTH, HEIGHT, &color);
 
-    display.flush_all();
-
     display.put_upper_than(&window_layer, &background_layer);
-    display.flush_area((0, 110), (0, 60));
+    display.flush();
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         window
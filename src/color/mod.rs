@@ -0,0 +1,863 @@
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::fmt;
+use spin::Mutex;
+
+mod parse;
+mod space;
+
+pub use space::*;
+
+/// This enum stores colors. \
+/// Supported formats:
+/// - RGB
+/// - ARGB
+/// - BGRA
+/// - 256 color mode(U8)
+///
+/// This enum allows you to convert colors into different formats easily.
+///
+/// ## Alpha
+/// Alpha is the opacity of a color: 0 is fully transparent and 0xff is fully opaque.
+/// Colors store straight (not premultiplied) alpha, and `Rgb` colors are always opaque. \
+/// Use `premultiplied` and `from_premultiplied` if you need the premultiplied representation.
+#[derive(Debug, Clone)]
+pub enum Color {
+    Rgb(u8, u8, u8),
+    Argb(u8, u8, u8, u8),
+    Bgra(u8, u8, u8, u8),
+    U8(Palette, u8),
+}
+
+impl Color {
+    pub fn new_rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self::Rgb(red, green, blue)
+    }
+
+    pub fn new_argb(alpha: u8, red: u8, green: u8, blue: u8) -> Self {
+        Self::Argb(alpha, red, green, blue)
+    }
+
+    pub fn new_bgra(blue: u8, green: u8, red: u8, alpha: u8) -> Self {
+        Self::Bgra(blue, green, red, alpha)
+    }
+
+    pub fn new_u8(palette: Palette, index: u8) -> Self {
+        Self::U8(palette, index)
+    }
+}
+
+impl Color {
+    pub fn red(&self) -> u8 {
+        match self {
+            &Self::Argb(_, red, _, _) => red,
+            &Self::Bgra(_, _, red, _) => red,
+            &Self::Rgb(red, _, _) => red,
+            Self::U8(palette, color_index) => palette.get_color(*color_index).red(),
+        }
+    }
+
+    pub fn blue(&self) -> u8 {
+        match self {
+            &Self::Argb(_, _, _, blue) => blue,
+            &Self::Bgra(blue, _, _, _) => blue,
+            &Self::Rgb(_, _, blue) => blue,
+            Self::U8(palette, color_index) => palette.get_color(*color_index).blue(),
+        }
+    }
+
+    pub fn green(&self) -> u8 {
+        match self {
+            &Self::Argb(_, _, green, _) => green,
+            &Self::Bgra(_, green, _, _) => green,
+            &Self::Rgb(_, green, _) => green,
+            Self::U8(palette, color_index) => palette.get_color(*color_index).green(),
+        }
+    }
+
+    pub fn alpha(&self) -> u8 {
+        match self {
+            &Self::Argb(alpha, _, _, _) => alpha,
+            &Self::Bgra(_, _, _, alpha) => alpha,
+            &Self::Rgb(_, _, _) => 0xff,
+            Self::U8(palette, color_index) => palette.get_color(*color_index).alpha(),
+        }
+    }
+}
+
+impl Color {
+    /// Put `other` over `self`, which is the Porter-Duff `src-over` operator with `other` as the source.
+    pub fn mix(&self, other: &Color) -> Self {
+        other.composite(self, CompositeOp::SrcOver)
+    }
+
+    /// Put `other` over `self` like `mix`, blending the channels in `space`.
+    pub fn mix_in(&self, other: &Color, space: BlendSpace) -> Self {
+        other.composite_in(self, CompositeOp::SrcOver, space)
+    }
+
+    /// Composite `self` as the source with `dst` as the destination.
+    pub fn composite(&self, dst: &Color, op: CompositeOp) -> Self {
+        self.composite_in(dst, op, BlendSpace::Srgb)
+    }
+
+    /// Composite `self` as the source with `dst` as the destination, blending the channels in `space`.
+    pub fn composite_in(&self, dst: &Color, op: CompositeOp, space: BlendSpace) -> Self {
+        let (src_alpha, src_red, src_green, src_blue) = self.as_argb_tuple();
+        let (dst_alpha, dst_red, dst_green, dst_blue) = dst.as_argb_tuple();
+        let (src_factor, dst_factor) = op.factors(src_alpha, dst_alpha);
+
+        // Weights of the source and the destination, scaled by 0xff * 0xff.
+        let src_weight = src_alpha as u32 * src_factor as u32;
+        let dst_weight = dst_alpha as u32 * dst_factor as u32;
+        let weight = src_weight + dst_weight;
+        if weight == 0 {
+            return Self::new_argb(0, 0, 0, 0);
+        }
+
+        let blend = |src: u8, dst: u8| match space {
+            BlendSpace::Srgb => {
+                ((src as u32 * src_weight + dst as u32 * dst_weight + weight / 2) / weight) as u8
+            }
+            BlendSpace::Linear => {
+                let (src, dst) = (srgb_to_linear(src) as u64, srgb_to_linear(dst) as u64);
+                let (src_weight, dst_weight, weight) =
+                    (src_weight as u64, dst_weight as u64, weight as u64);
+                linear_to_srgb(((src * src_weight + dst * dst_weight + weight / 2) / weight) as u16)
+            }
+        };
+        Self::new_argb(
+            ((weight + 0x7f) / 0xff).min(0xff) as u8,
+            blend(src_red, dst_red),
+            blend(src_green, dst_green),
+            blend(src_blue, dst_blue),
+        )
+    }
+
+    /// Blend `self` as the source with `dst` using `mode`, and then composite them with `op`. \
+    /// The blended color only shows where `dst` is opaque, as in the W3C compositing specification.
+    pub fn blend(&self, dst: &Color, mode: BlendMode, op: CompositeOp) -> Self {
+        self.blend_in(dst, mode, op, BlendSpace::Srgb)
+    }
+
+    /// Blend `self` with `dst` like `blend`, compositing the channels in `space`. \
+    /// `mode` itself is always applied to the sRGB values.
+    pub fn blend_in(
+        &self,
+        dst: &Color,
+        mode: BlendMode,
+        op: CompositeOp,
+        space: BlendSpace,
+    ) -> Self {
+        if mode == BlendMode::Normal {
+            return self.composite_in(dst, op, space);
+        }
+
+        let (src_alpha, src_red, src_green, src_blue) = self.as_argb_tuple();
+        let (dst_alpha, dst_red, dst_green, dst_blue) = dst.as_argb_tuple();
+        let mix = |backdrop: u8, source: u8| {
+            let blended = mode.blend_channel(backdrop, source);
+            (mul_div_255(source, 0xff - dst_alpha) as u32 + mul_div_255(blended, dst_alpha) as u32)
+                .min(0xff) as u8
+        };
+        Self::new_argb(
+            src_alpha,
+            mix(dst_red, src_red),
+            mix(dst_green, src_green),
+            mix(dst_blue, src_blue),
+        )
+        .composite_in(dst, op, space)
+    }
+
+    /// Multiply the alpha of the color by `opacity / 0xff`.
+    pub fn fade(&self, opacity: u8) -> Self {
+        let (alpha, red, green, blue) = self.as_argb_tuple();
+        Self::new_argb(mul_div_255(alpha, opacity), red, green, blue)
+    }
+
+    /// Get the color as `(alpha, red, green, blue)`, with the channels multiplied by alpha.
+    pub fn premultiplied(&self) -> (u8, u8, u8, u8) {
+        let (alpha, red, green, blue) = self.as_argb_tuple();
+        (
+            alpha,
+            mul_div_255(red, alpha),
+            mul_div_255(green, alpha),
+            mul_div_255(blue, alpha),
+        )
+    }
+
+    /// Create a color from channels which are multiplied by alpha. \
+    /// A fully transparent color becomes transparent black.
+    /// ## Example
+    /// ``` rust
+    /// use fur::color::Color;
+    ///
+    /// for alpha in 0..=0xff {
+    ///     for channel in 0..=0xff {
+    ///         // Premultiplied channels come back exactly.
+    ///         if channel <= alpha {
+    ///             let color = Color::from_premultiplied(alpha, channel, channel, channel);
+    ///             assert_eq!(color.premultiplied(), (alpha, channel, channel, channel));
+    ///         }
+    ///
+    ///         // Straight channels lose the precision which doesn't fit in the premultiplied value.
+    ///         let (_, red, green, blue) = Color::new_argb(alpha, channel, 0, 0xff).premultiplied();
+    ///         let color = Color::from_premultiplied(alpha, red, green, blue);
+    ///         match alpha {
+    ///             0 => assert_eq!(color.as_argb_tuple(), (0, 0, 0, 0)),
+    ///             0xff => assert_eq!(color.as_argb_tuple(), (0xff, channel, 0, 0xff)),
+    ///             _ => {
+    ///                 let error = (0xff + alpha as i32 - 1) / (2 * alpha as i32);
+    ///                 assert!((color.red() as i32 - channel as i32).abs() <= error);
+    ///                 assert_eq!((color.green(), color.blue()), (0, 0xff));
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn from_premultiplied(alpha: u8, red: u8, green: u8, blue: u8) -> Self {
+        if alpha == 0 {
+            return Self::new_argb(0, 0, 0, 0);
+        }
+        let unmultiply = |channel: u8| {
+            ((channel as u32 * 0xff + alpha as u32 / 2) / alpha as u32).min(0xff) as u8
+        };
+        Self::new_argb(alpha, unmultiply(red), unmultiply(green), unmultiply(blue))
+    }
+
+    pub fn as_rgb_tuple(&self) -> (u8, u8, u8) {
+        match self {
+            &Self::Argb(_, red, green, blue) => (red, green, blue),
+            &Self::Bgra(blue, green, red, _) => (red, green, blue),
+            &Self::Rgb(red, green, blue) => (red, green, blue),
+            Self::U8(palette, color_index) => palette.get_color(*color_index).as_rgb_tuple(),
+        }
+    }
+
+    pub fn as_argb_tuple(&self) -> (u8, u8, u8, u8) {
+        match self {
+            &Self::Argb(alpha, red, green, blue) => (alpha, red, green, blue),
+            &Self::Bgra(blue, green, red, alpha) => (alpha, red, green, blue),
+            &Self::Rgb(red, green, blue) => (0xff, red, green, blue),
+            Self::U8(palette, color_index) => palette.get_color(*color_index).as_argb_tuple(),
+        }
+    }
+
+    pub fn as_bgra_tuple(&self) -> (u8, u8, u8, u8) {
+        let (alpha, red, green, blue) = self.as_argb_tuple();
+        (blue, green, red, alpha)
+    }
+
+    pub fn as_u8(&self, palette: &Palette) -> Option<u8> {
+        palette.try_find_color(self.clone())
+    }
+}
+
+impl Color {
+    pub fn as_argb_u32(&self) -> u32 {
+        let (alpha, red, green, blue) = self.as_argb_tuple();
+        u32::from_be_bytes([alpha, red, green, blue])
+    }
+
+    pub fn as_bgra_u32(&self) -> u32 {
+        let (blue, green, red, alpha) = self.as_bgra_tuple();
+        u32::from_be_bytes([blue, green, red, alpha])
+    }
+
+    pub fn as_0rgb_u32(&self) -> u32 {
+        let (red, green, blue) = self.as_rgb_tuple();
+        u32::from_be_bytes([0, red, green, blue])
+    }
+
+    pub fn as_rgb0_u32(&self) -> u32 {
+        let (red, green, blue) = self.as_rgb_tuple();
+        u32::from_be_bytes([red, green, blue, 0])
+    }
+
+    pub fn as_bgr0_u32(&self) -> u32 {
+        let (red, green, blue) = self.as_rgb_tuple();
+        u32::from_be_bytes([blue, green, red, 0])
+    }
+
+    pub fn as_abgr_u32(&self) -> u32 {
+        let (alpha, red, green, blue) = self.as_argb_tuple();
+        u32::from_be_bytes([alpha, blue, green, red])
+    }
+
+    /// 5 bits red, 6 bits green and 5 bits blue, from the high bits to the low bits.
+    pub fn as_rgb565_u16(&self) -> u16 {
+        let (red, green, blue) = self.as_rgb_tuple();
+        (reduce_channel(red, 5) << 11) | (reduce_channel(green, 6) << 5) | reduce_channel(blue, 5)
+    }
+
+    /// 5 bits for each channel, from the high bits to the low bits. The highest bit is unused.
+    pub fn as_rgb555_u16(&self) -> u16 {
+        let (red, green, blue) = self.as_rgb_tuple();
+        (reduce_channel(red, 5) << 10) | (reduce_channel(green, 5) << 5) | reduce_channel(blue, 5)
+    }
+
+    /// The luma of the color, weighted as in ITU-R BT.601.
+    pub fn as_gray_u8(&self) -> u8 {
+        let (red, green, blue) = self.as_rgb_tuple();
+        ((red as u32 * 77 + green as u32 * 150 + blue as u32 * 29 + 0x80) >> 8) as u8
+    }
+}
+
+impl Color {
+    pub fn from_argb_u32(color: u32) -> Self {
+        let [alpha, red, green, blue] = color.to_be_bytes();
+        Self::new_argb(alpha, red, green, blue)
+    }
+
+    pub fn from_0rgb_u32(color: u32) -> Self {
+        let [_, red, green, blue] = color.to_be_bytes();
+        Self::new_rgb(red, green, blue)
+    }
+
+    pub fn from_rgb0_u32(color: u32) -> Self {
+        let [red, green, blue, _] = color.to_be_bytes();
+        Self::new_rgb(red, green, blue)
+    }
+
+    pub fn from_bgra_u32(color: u32) -> Self {
+        let [blue, green, red, alpha] = color.to_be_bytes();
+        Self::new_bgra(blue, green, red, alpha)
+    }
+
+    pub fn from_bgr0_u32(color: u32) -> Self {
+        let [blue, green, red, _] = color.to_be_bytes();
+        Self::new_rgb(red, green, blue)
+    }
+
+    pub fn from_abgr_u32(color: u32) -> Self {
+        let [alpha, blue, green, red] = color.to_be_bytes();
+        Self::new_argb(alpha, red, green, blue)
+    }
+
+    pub fn from_rgb565_u16(color: u16) -> Self {
+        Self::new_rgb(
+            expand_channel(color >> 11, 5),
+            expand_channel(color >> 5, 6),
+            expand_channel(color, 5),
+        )
+    }
+
+    pub fn from_rgb555_u16(color: u16) -> Self {
+        Self::new_rgb(
+            expand_channel(color >> 10, 5),
+            expand_channel(color >> 5, 5),
+            expand_channel(color, 5),
+        )
+    }
+
+    pub fn from_gray_u8(gray: u8) -> Self {
+        Self::new_rgb(gray, gray, gray)
+    }
+}
+
+/// Scale an 8 bits channel down to `bits` bits, with rounding.
+fn reduce_channel(channel: u8, bits: u32) -> u16 {
+    let max = (1 << bits) - 1;
+    ((channel as u32 * max + 0x7f) / 0xff) as u16
+}
+
+/// Scale the low `bits` bits of `channel` up to 8 bits.
+fn expand_channel(channel: u16, bits: u32) -> u8 {
+    let max = (1 << bits) - 1;
+    (((channel & max) as u32 * 0xff + max as u32 / 2) / max as u32) as u8
+}
+
+impl PartialEq for Color {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_argb_tuple() == other.as_argb_tuple()
+    }
+}
+
+/// Multiply two values in `0..=0xff` as if they were in `0.0..=1.0`, with rounding.
+fn mul_div_255(a: u8, b: u8) -> u8 {
+    let t = a as u32 * b as u32 + 0x80;
+    ((t + (t >> 8)) >> 8) as u8
+}
+
+/// The Porter-Duff compositing operators. \
+/// The source is the color being drawn, and the destination is the color already there.
+///
+/// Every operator is `result = source * Fs + destination * Fd` on premultiplied colors:
+///
+/// | Operator  | Fs          | Fd          |
+/// |-----------|-------------|-------------|
+/// | `Clear`   | 0           | 0           |
+/// | `Src`     | 1           | 0           |
+/// | `Dst`     | 0           | 1           |
+/// | `SrcOver` | 1           | 1 - src.a   |
+/// | `DstOver` | 1 - dst.a   | 1           |
+/// | `SrcIn`   | dst.a       | 0           |
+/// | `DstIn`   | 0           | src.a       |
+/// | `SrcOut`  | 1 - dst.a   | 0           |
+/// | `DstOut`  | 0           | 1 - src.a   |
+/// | `SrcAtop` | dst.a       | 1 - src.a   |
+/// | `DstAtop` | 1 - dst.a   | src.a       |
+/// | `Xor`     | 1 - dst.a   | 1 - src.a   |
+///
+/// ## Example
+/// Red as the source and blue as the destination, checked against values computed with floats. \
+/// The columns are: both opaque, a transparent source, a transparent destination,
+/// and a half transparent source over a 3/4 opaque destination.
+/// ``` rust
+/// use fur::color::{Color, CompositeOp};
+///
+/// let red = |alpha| Color::new_argb(alpha, 0xff, 0, 0);
+/// let blue = |alpha| Color::new_argb(alpha, 0, 0, 0xff);
+/// let inputs = [
+///     (red(0xff), blue(0xff)),
+///     (red(0), blue(0xff)),
+///     (red(0xff), blue(0)),
+///     (red(0x80), blue(0xc0)),
+/// ];
+///
+/// let (none, red, blue) = ((0, 0, 0, 0), (255, 255, 0, 0), (255, 0, 0, 255));
+/// let expected = [
+///     (CompositeOp::Clear, [none, none, none, none]),
+///     (CompositeOp::Src, [red, none, red, (128, 255, 0, 0)]),
+///     (CompositeOp::Dst, [blue, blue, none, (192, 0, 0, 255)]),
+///     (CompositeOp::SrcOver, [red, blue, red, (224, 146, 0, 109)]),
+///     (CompositeOp::DstOver, [blue, blue, red, (224, 36, 0, 219)]),
+///     (CompositeOp::SrcIn, [red, none, none, (96, 255, 0, 0)]),
+///     (CompositeOp::DstIn, [blue, none, none, (96, 0, 0, 255)]),
+///     (CompositeOp::SrcOut, [none, none, red, (32, 255, 0, 0)]),
+///     (CompositeOp::DstOut, [none, blue, none, (96, 0, 0, 255)]),
+///     (CompositeOp::SrcAtop, [red, blue, none, (192, 128, 0, 127)]),
+///     (CompositeOp::DstAtop, [blue, none, red, (128, 63, 0, 192)]),
+///     (CompositeOp::Xor, [none, blue, red, (127, 63, 0, 192)]),
+/// ];
+/// for (op, results) in expected {
+///     for ((src, dst), argb) in inputs.iter().zip(results) {
+///         assert_eq!(src.composite(dst, op).as_argb_tuple(), argb, "{:?} {:?} {:?}", op, src, dst);
+///     }
+/// }
+///
+/// // Opaque colors hide everything below them, and transparent ones change nothing.
+/// let background = Color::new_rgb(0x11, 0x45, 0x14);
+/// assert_eq!(background.mix(&Color::new_rgb(1, 2, 3)), Color::new_rgb(1, 2, 3));
+/// assert_eq!(background.mix(&Color::new_argb(0, 1, 2, 3)), background);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompositeOp {
+    Clear,
+    Src,
+    Dst,
+    #[default]
+    SrcOver,
+    DstOver,
+    SrcIn,
+    DstIn,
+    SrcOut,
+    DstOut,
+    SrcAtop,
+    DstAtop,
+    Xor,
+}
+
+impl CompositeOp {
+    /// Get `(Fs, Fd)` scaled to `0..=0xff`.
+    fn factors(&self, src_alpha: u8, dst_alpha: u8) -> (u8, u8) {
+        match self {
+            Self::Clear => (0, 0),
+            Self::Src => (0xff, 0),
+            Self::Dst => (0, 0xff),
+            Self::SrcOver => (0xff, 0xff - src_alpha),
+            Self::DstOver => (0xff - dst_alpha, 0xff),
+            Self::SrcIn => (dst_alpha, 0),
+            Self::DstIn => (0, src_alpha),
+            Self::SrcOut => (0xff - dst_alpha, 0),
+            Self::DstOut => (0, 0xff - src_alpha),
+            Self::SrcAtop => (dst_alpha, 0xff - src_alpha),
+            Self::DstAtop => (0xff - dst_alpha, src_alpha),
+            Self::Xor => (0xff - dst_alpha, 0xff - src_alpha),
+        }
+    }
+}
+
+/// The separable blend modes, which decide how the colors of a layer and the colors below it are mixed
+/// before they are composited.
+/// ## Example
+/// Every mode on a few backdrop and source channels, checked against values computed with floats.
+/// ``` rust
+/// use fur::color::{BlendMode, Color, CompositeOp};
+///
+/// let inputs = [(0x33, 0xcc), (0xcc, 0x33), (0x80, 0x80), (0, 0xff), (0xff, 0x40), (0x20, 0x40)];
+/// let expected = [
+///     (BlendMode::Normal, [204, 51, 128, 255, 64, 64]),
+///     (BlendMode::Multiply, [41, 41, 64, 0, 64, 8]),
+///     (BlendMode::Screen, [214, 214, 192, 255, 255, 88]),
+///     (BlendMode::Overlay, [82, 173, 128, 0, 255, 16]),
+///     (BlendMode::Add, [255, 255, 255, 255, 255, 96]),
+///     (BlendMode::Darken, [51, 51, 128, 0, 64, 32]),
+///     (BlendMode::Lighten, [204, 204, 128, 255, 255, 64]),
+///     (BlendMode::Difference, [153, 153, 0, 255, 191, 32]),
+/// ];
+/// for (mode, results) in expected {
+///     for (&(backdrop, source), result) in inputs.iter().zip(results) {
+///         let blended = mode.blend_channel(backdrop, source);
+///         assert_eq!(blended, result, "{:?} {} {}", mode, backdrop, source);
+///     }
+/// }
+///
+/// // Over an opaque backdrop the blended color is used as it is,
+/// // and over a transparent one the source shows unchanged.
+/// let source = Color::new_rgb(0x33, 0x80, 0xff);
+/// let backdrop = Color::new_rgb(0xcc, 0x80, 0x40);
+/// let blended = source.blend(&backdrop, BlendMode::Multiply, CompositeOp::SrcOver);
+/// assert_eq!(blended.as_argb_tuple(), (0xff, 41, 64, 64));
+/// let transparent = Color::new_argb(0, 0xcc, 0x80, 0x40);
+/// let blended = source.blend(&transparent, BlendMode::Multiply, CompositeOp::SrcOver);
+/// assert_eq!(blended, source);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Use the source color.
+    #[default]
+    Normal,
+    /// `backdrop * source`, which always darkens.
+    Multiply,
+    /// `backdrop + source - backdrop * source`, which always lightens.
+    Screen,
+    /// `Multiply` where the backdrop is dark and `Screen` where it is light.
+    Overlay,
+    /// `backdrop + source`, clamped.
+    Add,
+    /// The darker one of the two colors.
+    Darken,
+    /// The lighter one of the two colors.
+    Lighten,
+    /// `|backdrop - source|`.
+    Difference,
+}
+
+impl BlendMode {
+    /// Blend one channel of the backdrop and the source.
+    pub fn blend_channel(&self, backdrop: u8, source: u8) -> u8 {
+        match self {
+            Self::Normal => source,
+            Self::Multiply => mul_div_255(backdrop, source),
+            Self::Screen => {
+                (backdrop as u32 + source as u32 - mul_div_255(backdrop, source) as u32) as u8
+            }
+            Self::Overlay => {
+                if backdrop < 0x80 {
+                    Self::Multiply.blend_channel(backdrop * 2, source)
+                } else {
+                    Self::Screen.blend_channel(backdrop - (0xff - backdrop), source)
+                }
+            }
+            Self::Add => backdrop.saturating_add(source),
+            Self::Darken => backdrop.min(source),
+            Self::Lighten => backdrop.max(source),
+            Self::Difference => backdrop.abs_diff(source),
+        }
+    }
+}
+
+/// This is the palette for 256 color mode. \
+/// It stores 256 colors, and you can use them through the index.
+///
+/// Looking up colors is cached, and the cache is shared by all the clones with the same colors.
+/// ## Example
+/// ``` rust
+/// use fur::color::{Color, Palette};
+///
+/// let palette = Palette::vga();
+/// assert_eq!(palette.get_color(4), Color::new_rgb(0xaa, 0, 0));
+/// assert_eq!(palette.find_nearest(&Color::new_rgb(0xb0, 0x08, 0x04)), 4);
+/// assert_eq!(Color::new_rgb(0xff, 0xff, 0xff).as_u8(&palette), Some(15));
+///
+/// // The result doesn't depend on the colors looked up before.
+/// let web_safe = Palette::web_safe();
+/// assert_eq!(web_safe.find_nearest(&Color::new_rgb(0x1a, 0, 0)), 36);
+/// assert_eq!(web_safe.find_nearest(&Color::new_rgb(0x19, 0, 0)), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colors: Arc<[Color; 256]>,
+    lookup: Arc<Mutex<Option<Lookup>>>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Palette {
+    /// Create a palette filled with white.
+    pub fn new() -> Self {
+        Self::from_fn(|_| Color::new_rgb(0xff, 0xff, 0xff))
+    }
+
+    /// Create a palette with the color returned by `f` for every index.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: FnMut(usize) -> Color,
+    {
+        Self {
+            colors: Arc::new(core::array::from_fn(f)),
+            lookup: Arc::default(),
+        }
+    }
+
+    /// The default palette of VGA mode 13h: the 16 EGA colors, 16 grays,
+    /// 216 colors in 3 intensities and 8 blacks.
+    pub fn vga() -> Self {
+        Self::from_fn(|index| match index {
+            0..=15 => ega_color(index),
+            16..=31 => {
+                let level = VGA_GRAYS[index - 16];
+                Color::new_rgb(level, level, level)
+            }
+            32..=247 => {
+                let ring = (index - 32) / 24;
+                let levels = VGA_RINGS[ring].map(|level| level << 2 | level >> 4);
+                hue_ring_color(&levels, (index - 32) % 24)
+            }
+            _ => Color::new_rgb(0, 0, 0),
+        })
+    }
+
+    /// The 16 colors of CGA and EGA text mode. The other entries are black.
+    pub fn ega() -> Self {
+        Self::from_fn(|index| match index {
+            0..=15 => ega_color(index),
+            _ => Color::new_rgb(0, 0, 0),
+        })
+    }
+
+    /// The 256 colors of xterm: 16 system colors, a 6x6x6 color cube and 24 grays.
+    pub fn xterm() -> Self {
+        const CUBE: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+        Self::from_fn(|index| match index {
+            0..=15 => {
+                let argb = XTERM_SYSTEM[index];
+                Color::new_rgb((argb >> 16) as u8, (argb >> 8) as u8, argb as u8)
+            }
+            16..=231 => {
+                let cube = index - 16;
+                Color::new_rgb(CUBE[cube / 36], CUBE[cube / 6 % 6], CUBE[cube % 6])
+            }
+            _ => {
+                let level = 8 + (index - 232) as u8 * 10;
+                Color::new_rgb(level, level, level)
+            }
+        })
+    }
+
+    /// The 216 web-safe colors, where the index is `red * 36 + green * 6 + blue` with each channel in `0..6`.
+    /// The other entries are black.
+    pub fn web_safe() -> Self {
+        Self::from_fn(|index| match index {
+            0..=215 => Color::new_rgb(
+                (index / 36) as u8 * 0x33,
+                (index / 6 % 6) as u8 * 0x33,
+                (index % 6) as u8 * 0x33,
+            ),
+            _ => Color::new_rgb(0, 0, 0),
+        })
+    }
+}
+
+impl Palette {
+    pub fn change_color(&mut self, index: u8, color: Color) -> &mut Self {
+        Arc::make_mut(&mut self.colors)[index as usize] = color;
+        self.lookup = Arc::default();
+        self
+    }
+
+    pub fn get_color(&self, index: u8) -> Color {
+        self.colors[index as usize].clone()
+    }
+
+    pub(crate) fn try_find_color(&self, required_color: Color) -> Option<u8> {
+        let argb = required_color.as_argb_u32();
+        self.with_lookup(|lookup| lookup.exact.get(&argb).copied())
+    }
+
+    /// Find the index of the color which is the nearest to `color` in RGB space. Alpha is ignored. \
+    /// If several entries are as near, the first one is returned. \
+    /// Only the entries which can be the nearest to a cell of 3 bits per channel are compared,
+    /// and they are found the first time a color of the cell is looked up.
+    pub fn find_nearest(&self, color: &Color) -> u8 {
+        let (red, green, blue) = color.as_rgb_tuple();
+        self.find_nearest_rgb(red, green, blue)
+    }
+
+    pub(crate) fn find_nearest_rgb(&self, red: u8, green: u8, blue: u8) -> u8 {
+        let rgb = (red as u32) << 16 | (green as u32) << 8 | blue as u32;
+        self.with_lookup(|lookup| {
+            if let Some(&index) = lookup.rgb.get(&rgb) {
+                return index;
+            }
+
+            let cell = [red, green, blue].map(|channel| channel >> Lookup::CELL_SHIFT);
+            let candidates = lookup
+                .cells
+                .entry(cell)
+                .or_insert_with(|| Lookup::candidates(&self.colors, cell));
+            self.search_nearest(candidates, red, green, blue)
+        })
+    }
+
+    /// Compare the color with the entries at `indices`.
+    fn search_nearest(&self, indices: &[u8], red: u8, green: u8, blue: u8) -> u8 {
+        let distance = |color: &Color| {
+            let (r, g, b) = color.as_rgb_tuple();
+            let (dr, dg, db) = (
+                r as i32 - red as i32,
+                g as i32 - green as i32,
+                b as i32 - blue as i32,
+            );
+            dr * dr + dg * dg + db * db
+        };
+        indices
+            .iter()
+            .copied()
+            .min_by_key(|index| distance(&self.colors[*index as usize]))
+            .unwrap_or(0)
+    }
+
+    fn with_lookup<T, F>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut Lookup) -> T,
+    {
+        let mut lookup = self.lookup.lock();
+        f(lookup.get_or_insert_with(|| Lookup::new(&self.colors)))
+    }
+
+    /// Check if two palettes share the same colors without comparing them.
+    pub(crate) fn ptr_eq(&self, other: &Palette) -> bool {
+        Arc::ptr_eq(&self.colors, &other.colors)
+    }
+}
+
+impl Palette {
+    /// Get all the colors as `(red, green, blue)`.
+    pub fn as_rgb_array(&self) -> [(u8, u8, u8); 256] {
+        core::array::from_fn(|index| self.colors[index].as_rgb_tuple())
+    }
+
+    /// Get all the colors with 6 bits channels, which can be written to the DAC of a VGA card.
+    pub fn as_vga_dac(&self) -> [[u8; 3]; 256] {
+        core::array::from_fn(|index| {
+            let (red, green, blue) = self.colors[index].as_rgb_tuple();
+            [red >> 2, green >> 2, blue >> 2]
+        })
+    }
+}
+
+/// The cached results of looking up colors in a palette.
+struct Lookup {
+    /// The first index of every ARGB value in the palette.
+    exact: BTreeMap<u32, u8>,
+    /// The first index of every RGB value in the palette.
+    rgb: BTreeMap<u32, u8>,
+    /// The entries which can be the nearest to a color of a cell, for the cells which have been used.
+    cells: BTreeMap<[u8; 3], Vec<u8>>,
+}
+
+impl Lookup {
+    /// Cells keep the 3 high bits of every channel.
+    const CELL_SHIFT: u32 = 5;
+
+    fn new(colors: &[Color; 256]) -> Self {
+        let mut exact = BTreeMap::new();
+        let mut rgb = BTreeMap::new();
+        for (index, color) in colors.iter().enumerate().rev() {
+            let argb = color.as_argb_u32();
+            exact.insert(argb, index as u8);
+            rgb.insert(argb & 0xffffff, index as u8);
+        }
+        Self {
+            exact,
+            rgb,
+            cells: BTreeMap::new(),
+        }
+    }
+
+    /// Find the entries which can be the nearest to a color of a cell, in the order of their indices. \
+    /// No color of the cell is farther from its nearest entry than from the entry whose farthest corner is the nearest,
+    /// so entries which are farther than that from the whole cell are left out.
+    fn candidates(colors: &[Color; 256], cell: [u8; 3]) -> Vec<u8> {
+        let size = 1 << Self::CELL_SHIFT;
+        let distances = colors
+            .iter()
+            .map(|color| {
+                let (red, green, blue) = color.as_rgb_tuple();
+                [red, green, blue].into_iter().zip(cell).fold(
+                    (0, 0),
+                    |(near, far), (channel, cell)| {
+                        let channel = channel as i32;
+                        let low = (cell as i32) << Self::CELL_SHIFT;
+                        let high = low + size - 1;
+                        let nearest = (low - channel).max(channel - high).max(0);
+                        let farthest = (channel - low).max(high - channel);
+                        (near + nearest * nearest, far + farthest * farthest)
+                    },
+                )
+            })
+            .collect::<Vec<(i32, i32)>>();
+        let limit = distances.iter().map(|(_, far)| *far).min().unwrap_or(0);
+        (0..=255)
+            .filter(|index| distances[*index as usize].0 <= limit)
+            .collect()
+    }
+}
+
+impl fmt::Debug for Lookup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lookup").finish_non_exhaustive()
+    }
+}
+
+/// The 16 colors of CGA and EGA, where brown is the only one with a half intensity green.
+fn ega_color(index: usize) -> Color {
+    let high = if index & 8 != 0 { 0x55 } else { 0 };
+    let channel = |bit: usize| if index & bit != 0 { 0xaa + high } else { high };
+    let green = if index == 6 { 0x55 } else { channel(2) };
+    Color::new_rgb(channel(4), green, channel(1))
+}
+
+/// Get a color of the 24 hues in the VGA palette, going from blue through red, yellow, green and cyan.
+/// `levels` are the 5 intensities from the lowest to the highest.
+fn hue_ring_color(levels: &[u8; 5], hue: usize) -> Color {
+    let (low, high) = (levels[0], levels[4]);
+    let (up, down) = (levels[hue % 4], levels[4 - hue % 4]);
+    match hue / 4 {
+        0 => Color::new_rgb(up, low, high),
+        1 => Color::new_rgb(high, low, down),
+        2 => Color::new_rgb(high, up, low),
+        3 => Color::new_rgb(down, high, low),
+        4 => Color::new_rgb(low, high, up),
+        _ => Color::new_rgb(low, down, high),
+    }
+}
+
+/// The grays of the VGA palette.
+const VGA_GRAYS: [u8; 16] = [
+    0x00, 0x14, 0x20, 0x2c, 0x38, 0x45, 0x51, 0x61, 0x71, 0x82, 0x92, 0xa2, 0xb6, 0xcb, 0xe3, 0xff,
+];
+
+/// The intensities of the 9 hue rings in the VGA palette in 6 bits.
+const VGA_RINGS: [[u8; 5]; 9] = [
+    [0x00, 0x10, 0x1f, 0x2f, 0x3f],
+    [0x1f, 0x27, 0x2f, 0x37, 0x3f],
+    [0x2d, 0x31, 0x36, 0x3a, 0x3f],
+    [0x00, 0x07, 0x0e, 0x15, 0x1c],
+    [0x0e, 0x11, 0x15, 0x18, 0x1c],
+    [0x14, 0x16, 0x18, 0x1a, 0x1c],
+    [0x00, 0x04, 0x08, 0x0c, 0x10],
+    [0x08, 0x0a, 0x0c, 0x0e, 0x10],
+    [0x0b, 0x0c, 0x0d, 0x0f, 0x10],
+];
+
+/// The 16 system colors of xterm as `0x00RRGGBB`.
+const XTERM_SYSTEM: [u32; 16] = [
+    0x000000, 0x800000, 0x008000, 0x808000, 0x000080, 0x800080, 0x008080, 0xc0c0c0, 0x808080,
+    0xff0000, 0x00ff00, 0xffff00, 0x0000ff, 0xff00ff, 0x00ffff, 0xffffff,
+];
@@ -0,0 +1,262 @@
+use super::Color;
+
+/// The space where the channels of colors are blended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendSpace {
+    /// Blend the gamma encoded sRGB values directly. It is fast, but mixed colors look darker than they should.
+    #[default]
+    Srgb,
+    /// Convert the channels to linear light before blending, and back to sRGB after it.
+    Linear,
+}
+
+/// HSL and HSV.
+impl Color {
+    /// Create an opaque color from hue, saturation and lightness. \
+    /// Hue is in degrees, from 0 to 359. Saturation and lightness are from 0 to 0xff. \
+    /// The conversion uses integer math only, and a round trip through `to_hsl` may be off by a few levels
+    /// because hue is rounded to whole degrees.
+    /// ## Example
+    /// ``` rust
+    /// use fur::color::Color;
+    ///
+    /// assert_eq!(Color::from_hsl(30, 0xff, 0x80).as_rgb_tuple(), (0xff, 0x80, 0x01));
+    /// assert_eq!(Color::from_hsl(120, 0xff, 0x40).as_rgb_tuple(), (0, 0x80, 0));
+    /// assert_eq!(Color::from_hsl(200, 0, 0x80).as_rgb_tuple(), (0x80, 0x80, 0x80));
+    /// assert_eq!(Color::from_hsl(0, 0xff, 0xff).as_rgb_tuple(), (0xff, 0xff, 0xff));
+    /// ```
+    pub fn from_hsl(hue: u16, saturation: u8, lightness: u8) -> Self {
+        let (saturation, lightness) = (saturation as i32, lightness as i32);
+        // The chroma and the minimum of the channels, scaled by 0xff * 2.
+        let chroma = (0xff - (2 * lightness - 0xff).abs()) * saturation * 2;
+        let min = lightness * 0xff * 2 - chroma / 2;
+        from_hue(hue, chroma, min)
+    }
+
+    /// Create an opaque color from hue, saturation and value, in the same ranges as `from_hsl`.
+    /// ## Example
+    /// ``` rust
+    /// use fur::color::Color;
+    ///
+    /// assert_eq!(Color::from_hsv(240, 0xff, 0xff), Color::new_rgb(0, 0, 0xff));
+    /// assert_eq!(Color::new_rgb(0, 0, 0xff).to_hsv(), (240, 0xff, 0xff));
+    /// ```
+    pub fn from_hsv(hue: u16, saturation: u8, value: u8) -> Self {
+        let (saturation, value) = (saturation as i32, value as i32);
+        let chroma = value * saturation * 2;
+        let min = value * 0xff * 2 - chroma;
+        from_hue(hue, chroma, min)
+    }
+
+    /// Get the color as `(hue, saturation, lightness)`. Alpha is ignored.
+    /// ## Example
+    /// ``` rust
+    /// use fur::color::Color;
+    ///
+    /// assert_eq!(Color::new_rgb(0xff, 0x80, 0x00).to_hsl(), (30, 0xff, 0x80));
+    /// assert_eq!(Color::new_argb(0x40, 0, 0x80, 0).to_hsl(), (120, 0xff, 0x40));
+    /// assert_eq!(Color::new_rgb(0x80, 0x80, 0x80).to_hsl(), (0, 0, 0x80));
+    ///
+    /// // Every color comes back within a few levels.
+    /// for rgb in (0..0x1000000).step_by(0x10101) {
+    ///     let color = Color::from_0rgb_u32(rgb);
+    ///     let (hue, saturation, lightness) = color.to_hsl();
+    ///     let (red, green, blue) = Color::from_hsl(hue, saturation, lightness).as_rgb_tuple();
+    ///     let (old_red, old_green, old_blue) = color.as_rgb_tuple();
+    ///     assert!(red.abs_diff(old_red) <= 3, "{:?}", color);
+    ///     assert!(green.abs_diff(old_green) <= 3, "{:?}", color);
+    ///     assert!(blue.abs_diff(old_blue) <= 3, "{:?}", color);
+    /// }
+    /// ```
+    pub fn to_hsl(&self) -> (u16, u8, u8) {
+        let (red, green, blue) = self.as_rgb_tuple();
+        let (max, min) = (
+            red.max(green).max(blue) as i32,
+            red.min(green).min(blue) as i32,
+        );
+        let lightness = ((max + min + 1) / 2) as u8;
+        if max == min {
+            return (0, 0, lightness);
+        }
+
+        let delta = max - min;
+        let divisor = 0xff - (max + min - 0xff).abs();
+        let saturation = ((delta * 0xff + divisor / 2) / divisor).min(0xff) as u8;
+        (to_hue(red, green, blue), saturation, lightness)
+    }
+
+    /// Get the color as `(hue, saturation, value)`. Alpha is ignored.
+    pub fn to_hsv(&self) -> (u16, u8, u8) {
+        let (red, green, blue) = self.as_rgb_tuple();
+        let (max, min) = (
+            red.max(green).max(blue) as i32,
+            red.min(green).min(blue) as i32,
+        );
+        if max == min {
+            return (0, 0, max as u8);
+        }
+
+        let saturation = (((max - min) * 0xff + max / 2) / max) as u8;
+        (to_hue(red, green, blue), saturation, max as u8)
+    }
+}
+
+impl Color {
+    /// Increase the lightness by `amount`, keeping hue, saturation and alpha.
+    /// ## Example
+    /// ``` rust
+    /// use fur::color::Color;
+    ///
+    /// assert_eq!(Color::new_rgb(0x80, 0x80, 0x80).lighten(0x20).as_rgb_tuple(), (0xa0, 0xa0, 0xa0));
+    /// ```
+    pub fn lighten(&self, amount: u8) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Self::from_hsl(hue, saturation, lightness.saturating_add(amount)).with_alpha_of(self)
+    }
+
+    /// Decrease the lightness by `amount`, keeping hue, saturation and alpha.
+    pub fn darken(&self, amount: u8) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Self::from_hsl(hue, saturation, lightness.saturating_sub(amount)).with_alpha_of(self)
+    }
+
+    /// Increase the saturation by `amount`, keeping hue, lightness and alpha.
+    pub fn saturate(&self, amount: u8) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Self::from_hsl(hue, saturation.saturating_add(amount), lightness).with_alpha_of(self)
+    }
+
+    /// Decrease the saturation by `amount`, keeping hue, lightness and alpha.
+    pub fn desaturate(&self, amount: u8) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        Self::from_hsl(hue, saturation.saturating_sub(amount), lightness).with_alpha_of(self)
+    }
+
+    fn with_alpha_of(self, other: &Color) -> Self {
+        match other.alpha() {
+            0xff => self,
+            alpha => {
+                let (red, green, blue) = self.as_rgb_tuple();
+                Self::new_argb(alpha, red, green, blue)
+            }
+        }
+    }
+}
+
+impl Color {
+    /// Get the channels in linear light, from 0 to 0xffff.
+    /// ## Example
+    /// ``` rust
+    /// use fur::color::Color;
+    ///
+    /// assert_eq!(Color::new_rgb(0, 0x80, 0xff).to_linear(), (0, 14146, 0xffff));
+    /// // 0xbc is the sRGB value of half the light of white.
+    /// assert_eq!(Color::new_rgb(0xbc, 0x0a, 0x01).to_linear(), (32957, 199, 20));
+    ///
+    /// // Every channel comes back exactly.
+    /// for channel in 0..=0xff {
+    ///     let color = Color::new_rgb(channel, 0, 0xff - channel);
+    ///     let (red, green, blue) = color.to_linear();
+    ///     assert_eq!(Color::from_linear(red, green, blue), color);
+    /// }
+    /// ```
+    pub fn to_linear(&self) -> (u16, u16, u16) {
+        let (red, green, blue) = self.as_rgb_tuple();
+        (
+            srgb_to_linear(red),
+            srgb_to_linear(green),
+            srgb_to_linear(blue),
+        )
+    }
+
+    /// Create an opaque color from channels in linear light, from 0 to 0xffff. \
+    /// Every channel becomes the sRGB value whose linear light is the nearest.
+    /// ## Example
+    /// ``` rust
+    /// use fur::color::Color;
+    ///
+    /// assert_eq!(Color::from_linear(0x8000, 0, 0xffff).as_rgb_tuple(), (0xbc, 0, 0xff));
+    /// assert_eq!(Color::from_linear(10, 31, 0x1000).as_rgb_tuple(), (0, 2, 0x47));
+    /// ```
+    pub fn from_linear(red: u16, green: u16, blue: u16) -> Self {
+        Self::new_rgb(
+            linear_to_srgb(red),
+            linear_to_srgb(green),
+            linear_to_srgb(blue),
+        )
+    }
+}
+
+/// Build a color from the hue, the chroma and the minimum channel, where the last two are scaled by 0xff * 2.
+fn from_hue(hue: u16, chroma: i32, min: i32) -> Color {
+    let hue = (hue % 360) as i32;
+    let second = (chroma * (60 - (hue % 120 - 60).abs()) + 30) / 60;
+    let (red, green, blue) = match hue / 60 {
+        0 => (chroma, second, 0),
+        1 => (second, chroma, 0),
+        2 => (0, chroma, second),
+        3 => (0, second, chroma),
+        4 => (second, 0, chroma),
+        _ => (chroma, 0, second),
+    };
+    let channel = |value: i32| ((value + min + 0xff) / (0xff * 2)).clamp(0, 0xff) as u8;
+    Color::new_rgb(channel(red), channel(green), channel(blue))
+}
+
+/// Get the hue of a color which is not gray.
+fn to_hue(red: u8, green: u8, blue: u8) -> u16 {
+    let (red, green, blue) = (red as i32, green as i32, blue as i32);
+    let (max, min) = (red.max(green).max(blue), red.min(green).min(blue));
+    let delta = max - min;
+    let (base, difference) = if max == red {
+        (0, green - blue)
+    } else if max == green {
+        (120, blue - red)
+    } else {
+        (240, red - green)
+    };
+    let hue = base * delta + 60 * difference;
+    ((hue + delta / 2).div_euclid(delta).rem_euclid(360)) as u16
+}
+
+pub(crate) fn srgb_to_linear(channel: u8) -> u16 {
+    SRGB_TO_LINEAR[channel as usize]
+}
+
+/// Find the sRGB value whose linear light is the nearest to `linear`.
+pub(crate) fn linear_to_srgb(linear: u16) -> u8 {
+    let index = SRGB_TO_LINEAR.partition_point(|&value| value < linear);
+    if index == 0 {
+        return 0;
+    }
+    if index == SRGB_TO_LINEAR.len() {
+        return 0xff;
+    }
+    if SRGB_TO_LINEAR[index] - linear < linear - SRGB_TO_LINEAR[index - 1] {
+        index as u8
+    } else {
+        (index - 1) as u8
+    }
+}
+
+/// The sRGB transfer function, scaled to 0xffff.
+const SRGB_TO_LINEAR: [u16; 256] = [
+    0, 20, 40, 60, 80, 99, 119, 139, 159, 179, 199, 219, 241, 264, 288, 313, 340, 367, 396, 427,
+    458, 491, 526, 562, 599, 637, 677, 718, 761, 805, 851, 898, 947, 997, 1048, 1101, 1156, 1212,
+    1270, 1330, 1391, 1453, 1517, 1583, 1651, 1720, 1790, 1863, 1937, 2013, 2090, 2170, 2250, 2333,
+    2418, 2504, 2592, 2681, 2773, 2866, 2961, 3058, 3157, 3258, 3360, 3464, 3570, 3678, 3788, 3900,
+    4014, 4129, 4247, 4366, 4488, 4611, 4736, 4864, 4993, 5124, 5257, 5392, 5530, 5669, 5810, 5953,
+    6099, 6246, 6395, 6547, 6700, 6856, 7014, 7174, 7335, 7500, 7666, 7834, 8004, 8177, 8352, 8528,
+    8708, 8889, 9072, 9258, 9445, 9635, 9828, 10022, 10219, 10417, 10619, 10822, 11028, 11235,
+    11446, 11658, 11873, 12090, 12309, 12530, 12754, 12980, 13209, 13440, 13673, 13909, 14146,
+    14387, 14629, 14874, 15122, 15371, 15623, 15878, 16135, 16394, 16656, 16920, 17187, 17456,
+    17727, 18001, 18277, 18556, 18837, 19121, 19407, 19696, 19987, 20281, 20577, 20876, 21177,
+    21481, 21787, 22096, 22407, 22721, 23038, 23357, 23678, 24002, 24329, 24658, 24990, 25325,
+    25662, 26001, 26344, 26688, 27036, 27386, 27739, 28094, 28452, 28813, 29176, 29542, 29911,
+    30282, 30656, 31033, 31412, 31794, 32179, 32567, 32957, 33350, 33745, 34143, 34544, 34948,
+    35355, 35764, 36176, 36591, 37008, 37429, 37852, 38278, 38706, 39138, 39572, 40009, 40449,
+    40891, 41337, 41785, 42236, 42690, 43147, 43606, 44069, 44534, 45002, 45473, 45947, 46423,
+    46903, 47385, 47871, 48359, 48850, 49344, 49841, 50341, 50844, 51349, 51858, 52369, 52884,
+    53401, 53921, 54445, 54971, 55500, 56032, 56567, 57105, 57646, 58190, 58737, 59287, 59840,
+    60396, 60955, 61517, 62082, 62650, 63221, 63795, 64372, 64952, 65535,
+];
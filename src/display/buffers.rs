@@ -1,117 +1,726 @@
 use alloc::{vec, vec::Vec};
+use core::slice;
 
-use crate::{color::Color, display::DisplayDriver, pixel::PixelFormat};
+use crate::{
+    color::{Color, Palette},
+    display::{read_spans, DisplayDriver},
+    error::{buffer_len, clip_area, Error, Result},
+    pixel::PixelFormat,
+};
 
-#[allow(dead_code)]
+/// A buffer in memory which stores pixels in the same layout as a framebuffer. \
+/// Pixels of every format except `U8` are supported, including the 3 bytes ones.
 pub struct DrawBuffer {
-    buffer: Vec<u32>,
+    buffer: Vec<u8>,
     width: usize,
     height: usize,
     pixel_format: PixelFormat,
 }
 
 impl DrawBuffer {
+    /// Create a draw buffer filled with zeros.
+    /// ## Panics
+    /// Panics if `try_new` fails.
     pub fn new(width: usize, height: usize, pixel_format: PixelFormat) -> Self {
-        debug_assert_ne!(
-            pixel_format,
-            PixelFormat::U8,
-            "U8 is not supported for DrawBuffer."
-        );
-        Self {
-            buffer: vec![0; width * height * 4],
+        match Self::try_new(width, height, pixel_format) {
+            Ok(draw_buffer) => draw_buffer,
+            Err(error) => panic!("Invalid draw buffer: {}", error),
+        }
+    }
+
+    /// Create a draw buffer, or return an error if the pixel format is not supported
+    /// or the number of bytes doesn't fit in `usize`.
+    pub fn try_new(width: usize, height: usize, pixel_format: PixelFormat) -> Result<Self> {
+        if pixel_format == PixelFormat::U8 {
+            return Err(Error::UnsupportedFormat(pixel_format));
+        }
+        Ok(Self {
+            buffer: zeroed(width, height, pixel_format.size())?,
             width,
             height,
             pixel_format,
-        }
+        })
+    }
+}
+
+impl DrawBuffer {
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.pixel_format
+    }
+
+    /// The pixels, row by row, which can be copied to a framebuffer of the same format.
+    pub fn data(&self) -> &[u8] {
+        &self.buffer
     }
 }
 
 impl DisplayDriver for DrawBuffer {
-    fn read(
-        &self,
-        x: usize,
-        y: usize,
+    fn read(&self, x: usize, y: usize, width: usize, height: usize, pixels: &mut [Color]) {
+        read_spans(self, x, y, width, height, pixels);
+    }
+
+    fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
+        let (width, height) = clip_area(self.size(), x, y, width, height);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let mut pixel = [0; 4];
+        self.pixel_format.encode(color, &mut pixel[..size]);
+
+        for dy in 0..height {
+            let start = ((y + dy) * self.width + x) * size;
+            for bytes in self.buffer[start..start + width * size].chunks_exact_mut(size) {
+                bytes.copy_from_slice(&pixel[..size]);
+            }
+        }
+    }
+
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let start = (y * self.width + x) * size;
+        let row = &self.buffer[start..start + width * size];
+        for (pixel, bytes) in pixels.iter_mut().zip(row.chunks_exact(size)) {
+            *pixel = self.pixel_format.decode(bytes);
+        }
+    }
+
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let start = (y * self.width + x) * size;
+        let row = &mut self.buffer[start..start + width * size];
+        for (bytes, pixel) in row.chunks_exact_mut(size).zip(pixels) {
+            self.pixel_format.encode(pixel, bytes);
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+/// A driver which draws directly into an existing framebuffer, such as the one a bootloader
+/// (Limine, multiboot, UEFI GOP) hands to the kernel, without copying it. \
+/// `pitch` is the number of bytes between the starts of two rows, which can be larger than
+/// `width * pixel_format.size()`.
+pub struct FramebufferDriver<'a> {
+    buffer: &'a mut [u8],
+    width: usize,
+    height: usize,
+    pitch: usize,
+    pixel_format: PixelFormat,
+}
+
+impl<'a> FramebufferDriver<'a> {
+    /// Wrap a framebuffer.
+    /// ## Panics
+    /// Panics if `try_new` fails.
+    pub fn new(
+        buffer: &'a mut [u8],
         width: usize,
         height: usize,
-        pixels: &mut [crate::color::Color],
-    ) {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-
-        for dx in 0..width {
-            for dy in 0..height {
-                let t_x = x + dx;
-                let t_y = y + dy;
-                pixels[dy * width + dx] = self
-                    .pixel_format
-                    .u32_as_color(self.buffer[t_y * self.width + t_x]);
-            }
+        pitch: usize,
+        pixel_format: PixelFormat,
+    ) -> Self {
+        match Self::try_new(buffer, width, height, pitch, pixel_format) {
+            Ok(driver) => driver,
+            Err(error) => panic!("Invalid framebuffer: {}", error),
         }
     }
 
+    /// Wrap a framebuffer, or return an error if the pixel format is not supported,
+    /// the size is empty or too large, `pitch` is shorter than a row or `buffer` is too small.
+    /// ## Example
+    /// ``` rust
+    /// use fur::{display::FramebufferDriver, error::Error, pixel::PixelFormat};
+    ///
+    /// const HUGE: usize = usize::MAX / 2;
+    /// let mut framebuffer = [0; 4 * 3 * 4];
+    /// assert!(FramebufferDriver::try_new(&mut framebuffer, 4, 3, 16, PixelFormat::Argb).is_ok());
+    /// assert_eq!(
+    ///     FramebufferDriver::try_new(&mut framebuffer, 4, 3, 12, PixelFormat::Argb).err(),
+    ///     Some(Error::InvalidPitch { pitch: 12, row: 16 })
+    /// );
+    /// assert_eq!(
+    ///     FramebufferDriver::try_new(&mut framebuffer, 4, 4, 16, PixelFormat::Argb).err(),
+    ///     Some(Error::BufferTooSmall { required: 64, actual: 48 })
+    /// );
+    /// assert_eq!(
+    ///     FramebufferDriver::try_new(&mut framebuffer, 4, 0, 16, PixelFormat::Argb).err(),
+    ///     Some(Error::InvalidSize { width: 4, height: 0 })
+    /// );
+    /// assert_eq!(
+    ///     FramebufferDriver::try_new(&mut framebuffer, HUGE, 1, 0, PixelFormat::Argb).err(),
+    ///     Some(Error::InvalidSize { width: HUGE, height: 1 })
+    /// );
+    /// assert_eq!(
+    ///     FramebufferDriver::try_new(&mut framebuffer, 4, HUGE, 16, PixelFormat::Argb).err(),
+    ///     Some(Error::InvalidSize { width: 4, height: HUGE })
+    /// );
+    /// ```
+    pub fn try_new(
+        buffer: &'a mut [u8],
+        width: usize,
+        height: usize,
+        pitch: usize,
+        pixel_format: PixelFormat,
+    ) -> Result<Self> {
+        if pixel_format == PixelFormat::U8 {
+            return Err(Error::UnsupportedFormat(pixel_format));
+        }
+        let invalid_size = Error::InvalidSize { width, height };
+        if width == 0 || height == 0 {
+            return Err(invalid_size);
+        }
+        let row = width.checked_mul(pixel_format.size()).ok_or(invalid_size)?;
+        if pitch < row {
+            return Err(Error::InvalidPitch { pitch, row });
+        }
+        let required = pitch
+            .checked_mul(height - 1)
+            .and_then(|rows| rows.checked_add(row))
+            .ok_or(invalid_size)?;
+        if buffer.len() < required {
+            return Err(Error::BufferTooSmall {
+                required,
+                actual: buffer.len(),
+            });
+        }
+
+        Ok(Self {
+            buffer,
+            width,
+            height,
+            pitch,
+            pixel_format,
+        })
+    }
+
+    /// Wrap a framebuffer from its address.
+    /// ## Safety
+    /// `address` must point to `pitch * height` bytes which are valid for reading and writing
+    /// during `'a`, and nothing else may access them at the same time.
+    pub unsafe fn from_raw_parts(
+        address: *mut u8,
+        width: usize,
+        height: usize,
+        pitch: usize,
+        pixel_format: PixelFormat,
+    ) -> Result<Self> {
+        let len = pitch
+            .checked_mul(height)
+            .filter(|&len| len <= isize::MAX as usize)
+            .ok_or(Error::InvalidSize { width, height })?;
+        let buffer = slice::from_raw_parts_mut(address, len);
+        Self::try_new(buffer, width, height, pitch, pixel_format)
+    }
+}
+
+impl FramebufferDriver<'_> {
+    pub fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.pixel_format
+    }
+}
+
+impl DisplayDriver for FramebufferDriver<'_> {
+    fn read(&self, x: usize, y: usize, width: usize, height: usize, pixels: &mut [Color]) {
+        read_spans(self, x, y, width, height, pixels);
+    }
+
     fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-
-        for dx in 0..width {
-            for dy in 0..height {
-                let t_x = x + dx;
-                let t_y = y + dy;
-                self.buffer[t_y * self.width + t_x] = self.pixel_format.color_as_u32(color);
+        let (width, height) = clip_area(self.size(), x, y, width, height);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let mut pixel = [0; 4];
+        self.pixel_format.encode(color, &mut pixel[..size]);
+
+        for dy in 0..height {
+            let start = (y + dy) * self.pitch + x * size;
+            for bytes in self.buffer[start..start + width * size].chunks_exact_mut(size) {
+                bytes.copy_from_slice(&pixel[..size]);
+            }
+        }
+    }
+
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let start = y * self.pitch + x * size;
+        let row = &self.buffer[start..start + width * size];
+        for (pixel, bytes) in pixels.iter_mut().zip(row.chunks_exact(size)) {
+            *pixel = self.pixel_format.decode(bytes);
+        }
+    }
+
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let start = y * self.pitch + x * size;
+        let row = &mut self.buffer[start..start + width * size];
+        for (bytes, pixel) in row.chunks_exact_mut(size).zip(pixels) {
+            self.pixel_format.encode(pixel, bytes);
+        }
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+/// How a `PaletteBuffer` hides the difference between a color and its nearest palette entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dithering {
+    /// Always use the nearest entry.
+    #[default]
+    None,
+    /// Add a 4x4 Bayer threshold pattern before looking for the nearest entry. \
+    /// The pattern spans about 60 levels per channel, which suits palettes like the 6x6x6 color cube.
+    Ordered,
+    /// Spread the error of each pixel to its right and lower neighbours in the same write.
+    FloydSteinberg,
+}
+
+/// A driver for 256 color modes, such as VGA mode 13h. \
+/// It stores the indices of a palette, and colors written to it are converted to the nearest entry.
+/// ## Example
+/// ``` rust
+/// use fur::color::{Color, Palette};
+/// use fur::display::{DisplayDriver, PaletteBuffer};
+///
+/// let mut palette = Palette::new();
+/// palette.change_color(0, Color::new_rgb(0, 0, 0));
+/// palette.change_color(1, Color::new_rgb(0xff, 0, 0));
+/// let mut buffer = PaletteBuffer::new(4, 4, palette.clone());
+///
+/// buffer.write(0, 0, 2, 2, &Color::new_rgb(0xe0, 0x10, 0x10));
+/// assert_eq!(buffer.data()[0], 1);
+/// assert_eq!(palette.as_vga_dac()[1], [0x3f, 0, 0]);
+///
+/// let huge = usize::MAX / 2;
+/// assert!(PaletteBuffer::try_new(huge, 3, palette).is_err());
+/// ```
+pub struct PaletteBuffer {
+    buffer: Vec<u8>,
+    width: usize,
+    height: usize,
+    palette: Palette,
+    dithering: Dithering,
+}
+
+impl PaletteBuffer {
+    /// Create a buffer filled with index 0.
+    /// ## Panics
+    /// Panics if `try_new` fails.
+    pub fn new(width: usize, height: usize, palette: Palette) -> Self {
+        match Self::try_new(width, height, palette) {
+            Ok(palette_buffer) => palette_buffer,
+            Err(error) => panic!("Invalid palette buffer: {}", error),
+        }
+    }
+
+    /// Create a buffer, or return an error if the number of pixels doesn't fit in `usize`.
+    pub fn try_new(width: usize, height: usize, palette: Palette) -> Result<Self> {
+        Ok(Self {
+            buffer: zeroed(width, height, 1)?,
+            width,
+            height,
+            palette,
+            dithering: Dithering::None,
+        })
+    }
+}
+
+impl PaletteBuffer {
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Replace the palette. The indices are kept, so the colors change just like reprogramming the DAC.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    pub fn dithering(&self) -> Dithering {
+        self.dithering
+    }
+
+    pub fn set_dithering(&mut self, dithering: Dithering) {
+        self.dithering = dithering;
+    }
+
+    /// The indices, row by row, which can be copied to the video memory.
+    pub fn data(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl PaletteBuffer {
+    const BAYER: [[i32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+    /// Get the index of a color which is already in the palette of this buffer.
+    fn palette_index(&self, color: &Color) -> Option<u8> {
+        match color {
+            Color::U8(palette, index) if palette.ptr_eq(&self.palette) => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Convert the colors of an area to indices and store them. \
+    /// `color_at` gets the color at `(dx, dy)` of the area, and the area must be inside the buffer.
+    fn quantize<F>(&mut self, x: usize, y: usize, width: usize, height: usize, color_at: F)
+    where
+        F: Fn(usize, usize) -> Color,
+    {
+        // Errors of the current row and the next row for Floyd-Steinberg, with one pixel of padding on each side.
+        let mut errors = match self.dithering {
+            Dithering::FloydSteinberg => vec![[0; 3]; (width + 2) * 2],
+            _ => Vec::new(),
+        };
+
+        for dy in 0..height {
+            for dx in 0..width {
+                let color = color_at(dx, dy);
+                let offset = (y + dy) * self.width + x + dx;
+                if let Some(index) = self.palette_index(&color) {
+                    self.buffer[offset] = index;
+                    continue;
+                }
+
+                let (red, green, blue) = color.as_rgb_tuple();
+                let mut channels = [red as i32, green as i32, blue as i32];
+                match self.dithering {
+                    Dithering::None => {}
+                    Dithering::Ordered => {
+                        let threshold = Self::BAYER[(y + dy) % 4][(x + dx) % 4] * 2 - 15;
+                        for channel in channels.iter_mut() {
+                            *channel += threshold * 2;
+                        }
+                    }
+                    Dithering::FloydSteinberg => {
+                        let error = errors[dx + 1];
+                        for (channel, error) in channels.iter_mut().zip(error) {
+                            *channel += error / 16;
+                        }
+                    }
+                }
+
+                let [red, green, blue] = channels.map(|channel| channel.clamp(0, 0xff) as u8);
+                let index = self.palette.find_nearest_rgb(red, green, blue);
+                self.buffer[offset] = index;
+
+                if self.dithering == Dithering::FloydSteinberg {
+                    let (r, g, b) = self.palette.get_color(index).as_rgb_tuple();
+                    let error = [
+                        channels[0] - r as i32,
+                        channels[1] - g as i32,
+                        channels[2] - b as i32,
+                    ];
+                    let next_row = width + 2;
+                    for (i, error) in error.into_iter().enumerate() {
+                        errors[dx + 2][i] += error * 7;
+                        errors[next_row + dx][i] += error * 3;
+                        errors[next_row + dx + 1][i] += error * 5;
+                        errors[next_row + dx + 2][i] += error;
+                    }
+                }
+            }
+
+            if self.dithering == Dithering::FloydSteinberg {
+                let (current, next) = errors.split_at_mut(width + 2);
+                current.copy_from_slice(next);
+                next.fill([0; 3]);
             }
         }
     }
+}
+
+impl DisplayDriver for PaletteBuffer {
+    fn read(&self, x: usize, y: usize, width: usize, height: usize, pixels: &mut [Color]) {
+        read_spans(self, x, y, width, height, pixels);
+    }
+
+    fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
+        let (width, height) = clip_area(self.size(), x, y, width, height);
+        self.quantize(x, y, width, height, |_, _| color.clone());
+    }
+
+    fn write_pixels(&mut self, x: usize, y: usize, width: usize, height: usize, pixels: &[Color]) {
+        let (clipped_width, clipped_height) = clip_area(self.size(), x, y, width, height);
+        let clipped_height = clipped_height.min(pixels.len() / width.max(1));
+        self.quantize(x, y, clipped_width, clipped_height, |dx, dy| {
+            pixels[dy * width + dx].clone()
+        });
+    }
+
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let start = y * self.width + x;
+        for (pixel, index) in pixels.iter_mut().zip(&self.buffer[start..start + width]) {
+            *pixel = Color::new_u8(self.palette.clone(), *index);
+        }
+    }
+
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        self.quantize(x, y, width, height, |dx, _| pixels[dx].clone());
+    }
 
     fn size(&self) -> (usize, usize) {
         (self.width, self.height)
     }
 }
 
-#[allow(dead_code)]
+/// A buffer in memory which stores packed pixels of a `PixelFormat`. \
+/// Colors are only used when reading and writing, so a layer costs `width * height * pixel_format.size()` bytes.
 pub struct ColorBuffer {
-    buffer: Vec<Color>,
+    buffer: Vec<u8>,
     width: usize,
     height: usize,
+    pixel_format: PixelFormat,
 }
 
 impl ColorBuffer {
+    /// Create a buffer of `Argb` pixels, which is filled with black.
     pub fn new(width: usize, height: usize) -> Self {
-        Self {
-            buffer: vec![Color::new_rgb(0, 0, 0); width * height],
+        Self::with_format(width, height, PixelFormat::Argb)
+    }
+
+    /// Create a buffer of pixels in `pixel_format`, which is filled with black.
+    /// ## Panics
+    /// Panics if `try_with_format` fails.
+    pub fn with_format(width: usize, height: usize, pixel_format: PixelFormat) -> Self {
+        match Self::try_with_format(width, height, pixel_format) {
+            Ok(color_buffer) => color_buffer,
+            Err(error) => panic!("Invalid color buffer: {}", error),
+        }
+    }
+
+    /// Create a buffer, or return an error if the pixel format is not supported
+    /// or the number of bytes doesn't fit in `usize`.
+    pub fn try_with_format(width: usize, height: usize, pixel_format: PixelFormat) -> Result<Self> {
+        if pixel_format == PixelFormat::U8 {
+            return Err(Error::UnsupportedFormat(pixel_format));
+        }
+        let mut color_buffer = Self {
+            buffer: zeroed(width, height, pixel_format.size())?,
+            width,
+            height,
+            pixel_format,
+        };
+        color_buffer.write(0, 0, width, height, &Color::new_rgb(0, 0, 0));
+        Ok(color_buffer)
+    }
+}
+
+impl ColorBuffer {
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.pixel_format
+    }
+
+    /// The packed pixels, row by row.
+    pub fn data(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl ColorBuffer {
+    /// Reallocate the buffer with a new size. \
+    /// The old content is kept and placed according to `anchor`, and the new area is filled with `fill`.
+    /// ## Panics
+    /// Panics if `try_resize` fails.
+    pub fn resize(&mut self, width: usize, height: usize, anchor: Anchor, fill: &Color) {
+        if let Err(error) = self.try_resize(width, height, anchor, fill) {
+            panic!("Invalid color buffer: {}", error);
+        }
+    }
+
+    /// The same as `resize`, but it returns an error and keeps the buffer as it is
+    /// if the new size is too large to allocate.
+    /// ## Example
+    /// ``` rust
+    /// use fur::{
+    ///     color::Color,
+    ///     display::{Anchor, ColorBuffer, DisplayDriver},
+    ///     Error,
+    /// };
+    ///
+    /// let mut buffer = ColorBuffer::new(4, 4);
+    /// let black = Color::new_rgb(0, 0, 0);
+    /// assert!(buffer.try_resize(8, 2, Anchor::Center, &black).is_ok());
+    /// assert_eq!(buffer.size(), (8, 2));
+    /// assert_eq!(
+    ///     buffer.try_resize(usize::MAX / 2, 3, Anchor::TopLeft, &black),
+    ///     Err(Error::InvalidSize { width: usize::MAX / 2, height: 3 }),
+    /// );
+    /// assert_eq!(buffer.size(), (8, 2));
+    /// ```
+    pub fn try_resize(
+        &mut self,
+        width: usize,
+        height: usize,
+        anchor: Anchor,
+        fill: &Color,
+    ) -> Result<()> {
+        let size = self.pixel_format.size();
+        let mut resized = Self {
+            buffer: zeroed(width, height, size)?,
             width,
             height,
+            pixel_format: self.pixel_format,
+        };
+        resized.write(0, 0, width, height, fill);
+        let (offset_x, offset_y) = anchor.offset((self.width, self.height), (width, height));
+
+        for y in 0..self.height {
+            let t_y = y as isize + offset_y;
+            if t_y < 0 || t_y >= height as isize {
+                continue;
+            }
+            let x_start = (-offset_x).max(0) as usize;
+            let x_end = (width as isize - offset_x).clamp(0, self.width as isize) as usize;
+            if x_start >= x_end {
+                continue;
+            }
+
+            let src = (y * self.width + x_start) * size;
+            let dst = (t_y as usize * width + (x_start as isize + offset_x) as usize) * size;
+            let len = (x_end - x_start) * size;
+            resized.buffer[dst..dst + len].copy_from_slice(&self.buffer[src..src + len]);
         }
+
+        *self = resized;
+        Ok(())
+    }
+}
+
+/// Which part of the old content stays in place when a buffer or a layer is resized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Get where the old content starts in the new area.
+    fn offset(&self, old_size: (usize, usize), new_size: (usize, usize)) -> (isize, isize) {
+        let diff_x = new_size.0 as isize - old_size.0 as isize;
+        let diff_y = new_size.1 as isize - old_size.1 as isize;
+        let x = match self {
+            Self::TopLeft | Self::Left | Self::BottomLeft => 0,
+            Self::Top | Self::Center | Self::Bottom => diff_x / 2,
+            Self::TopRight | Self::Right | Self::BottomRight => diff_x,
+        };
+        let y = match self {
+            Self::TopLeft | Self::Top | Self::TopRight => 0,
+            Self::Left | Self::Center | Self::Right => diff_y / 2,
+            Self::BottomLeft | Self::Bottom | Self::BottomRight => diff_y,
+        };
+        (x, y)
     }
 }
 
 impl DisplayDriver for ColorBuffer {
     fn read(&self, x: usize, y: usize, width: usize, height: usize, pixels: &mut [Color]) {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-
-        for dx in 0..width {
-            for dy in 0..height {
-                let t_x = x + dx;
-                let t_y = y + dy;
-                pixels[dy * width + dx] = self.buffer[t_y * self.width + t_x].clone();
-            }
-        }
+        read_spans(self, x, y, width, height, pixels);
     }
 
     fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-
-        for dx in 0..width {
-            for dy in 0..height {
-                let t_x = x + dx;
-                let t_y = y + dy;
-                self.buffer[t_y * self.width + t_x] = color.clone();
+        let (width, height) = clip_area(self.size(), x, y, width, height);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let mut pixel = [0; 4];
+        self.pixel_format.encode(color, &mut pixel[..size]);
+
+        for dy in 0..height {
+            let start = ((y + dy) * self.width + x) * size;
+            for bytes in self.buffer[start..start + width * size].chunks_exact_mut(size) {
+                bytes.copy_from_slice(&pixel[..size]);
             }
         }
     }
 
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let start = (y * self.width + x) * size;
+        let row = &self.buffer[start..start + width * size];
+        for (pixel, bytes) in pixels.iter_mut().zip(row.chunks_exact(size)) {
+            *pixel = self.pixel_format.decode(bytes);
+        }
+    }
+
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let size = self.pixel_format.size();
+        let start = (y * self.width + x) * size;
+        let row = &mut self.buffer[start..start + width * size];
+        for (bytes, pixel) in row.chunks_exact_mut(size).zip(pixels) {
+            self.pixel_format.encode(pixel, bytes);
+        }
+    }
+
     fn size(&self) -> (usize, usize) {
         (self.width, self.height)
     }
 }
+
+/// Allocate a buffer of `width * height` pixels of `pixel_size` bytes, filled with zeros. \
+/// Returns an error instead of aborting if the size overflows or the memory can't be allocated.
+fn zeroed(width: usize, height: usize, pixel_size: usize) -> Result<Vec<u8>> {
+    let len = buffer_len(width, height, pixel_size)?;
+    let mut buffer = Vec::new();
+    buffer
+        .try_reserve_exact(len)
+        .map_err(|_| Error::InvalidSize { width, height })?;
+    buffer.resize(len, 0);
+    Ok(buffer)
+}
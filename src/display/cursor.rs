@@ -0,0 +1,364 @@
+use alloc::vec::Vec;
+
+use crate::color::Color;
+
+use super::{Display, DisplayDriver, Layer, Rect};
+
+/// The cursors Fur can draw without any image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorShape {
+    #[default]
+    Arrow,
+    /// For text which can be selected or edited.
+    IBeam,
+    /// Resizing to the left or the right.
+    ResizeHorizontal,
+    /// Resizing up or down.
+    ResizeVertical,
+    /// Resizing from the top left or the bottom right corner.
+    ResizeDiagonal,
+    /// Resizing from the top right or the bottom left corner.
+    ResizeAntiDiagonal,
+    Busy,
+}
+
+/// The image of a mouse cursor. \
+/// The hot spot is the pixel of the image which points at the position of the cursor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    width: usize,
+    height: usize,
+    hot_spot: (usize, usize),
+    pixels: Vec<Color>,
+}
+
+impl Cursor {
+    /// Create a cursor from pixels stored row by row. Transparent pixels let the display show through. \
+    /// Missing pixels are transparent, and the hot spot is clamped into the image.
+    pub fn new(width: usize, height: usize, hot_spot: (usize, usize), pixels: &[Color]) -> Self {
+        let mut pixels = pixels.to_vec();
+        pixels.resize(width * height, Color::new_argb(0, 0, 0, 0));
+        Self {
+            width,
+            height,
+            hot_spot: (
+                hot_spot.0.min(width.saturating_sub(1)),
+                hot_spot.1.min(height.saturating_sub(1)),
+            ),
+            pixels,
+        }
+    }
+
+    /// Create a built-in cursor, which is white with a black outline.
+    pub fn from_shape(shape: CursorShape) -> Self {
+        let (rows, hot_spot): (&[&str], _) = match shape {
+            CursorShape::Arrow => (&ARROW, (0, 0)),
+            CursorShape::IBeam => (&I_BEAM, (3, 8)),
+            CursorShape::ResizeHorizontal => (&RESIZE_HORIZONTAL, (8, 4)),
+            CursorShape::ResizeVertical => {
+                return Self::from_ascii(&RESIZE_HORIZONTAL, (8, 4)).transposed()
+            }
+            CursorShape::ResizeDiagonal => (&RESIZE_DIAGONAL, (5, 5)),
+            CursorShape::ResizeAntiDiagonal => {
+                return Self::from_ascii(&RESIZE_DIAGONAL, (5, 5)).mirrored()
+            }
+            CursorShape::Busy => (&BUSY, (5, 8)),
+        };
+        Self::from_ascii(rows, hot_spot)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn hot_spot(&self) -> (usize, usize) {
+        self.hot_spot
+    }
+
+    /// The pixels of the image, row by row.
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+}
+
+impl Cursor {
+    /// `X` is the black outline, `.` is the white inside, and anything else is transparent.
+    fn from_ascii(rows: &[&str], hot_spot: (usize, usize)) -> Self {
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut pixels = Vec::with_capacity(width * rows.len());
+        for row in rows {
+            let mut bytes = row.bytes();
+            pixels.extend((0..width).map(|_| match bytes.next() {
+                Some(b'X') => Color::new_rgb(0, 0, 0),
+                Some(b'.') => Color::new_rgb(0xff, 0xff, 0xff),
+                _ => Color::new_argb(0, 0, 0, 0),
+            }));
+        }
+        Self::new(width, rows.len(), hot_spot, &pixels)
+    }
+
+    fn transposed(&self) -> Self {
+        let pixels = (0..self.width * self.height)
+            .map(|index| {
+                let (x, y) = (index / self.height, index % self.height);
+                self.pixels[y * self.width + x].clone()
+            })
+            .collect::<Vec<_>>();
+        let (x, y) = self.hot_spot;
+        Self::new(self.height, self.width, (y, x), &pixels)
+    }
+
+    fn mirrored(&self) -> Self {
+        let pixels = (0..self.width * self.height)
+            .map(|index| {
+                let (x, y) = (index % self.width, index / self.width);
+                self.pixels[y * self.width + self.width - 1 - x].clone()
+            })
+            .collect::<Vec<_>>();
+        let (x, y) = self.hot_spot;
+        Self::new(self.width, self.height, (self.width - 1 - x, y), &pixels)
+    }
+}
+
+/// The cursor of a display. It is drawn by the hardware if the driver supports it,
+/// otherwise in a layer which stays above all the other layers.
+pub(crate) struct CursorState {
+    cursor: Cursor,
+    shape: Option<CursorShape>,
+    position: (isize, isize),
+    visible: bool,
+    /// The layer the cursor is drawn in, or `None` if the hardware draws it.
+    layer: Option<Layer>,
+}
+
+/// The mouse cursor. \
+/// Moving a software cursor only damages its old and new rectangles, so `flush` composites nothing else.
+/// ## Example
+/// ``` rust
+/// use std::sync::Arc;
+///
+/// use fur::display::{CursorShape, Display, DrawBuffer};
+/// use fur::pixel::PixelFormat;
+/// use spin::RwLock;
+///
+/// let buffer = Arc::new(RwLock::new(DrawBuffer::new(320, 240, PixelFormat::Argb)));
+/// let mut display = Display::new(buffer);
+/// display.set_cursor_shape(CursorShape::Arrow);
+/// display.move_cursor(100, 80);
+/// display.flush();
+///
+/// let layer = display.create_layer(50, 50, 90, 70);
+/// assert_ne!(display.paint_order().next_back(), Some(&layer));
+/// assert_eq!(display.cursor_position(), (100, 80));
+/// ```
+impl Display {
+    /// Use `cursor` as the mouse cursor and show it. \
+    /// The driver draws it if `DisplayDriver::set_hardware_cursor` succeeds, otherwise it is drawn in a layer.
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        self.set_cursor_with_shape(cursor, None);
+    }
+
+    /// Use a built-in cursor. Nothing is drawn again if the shape doesn't change.
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        let current = self.cursor.as_ref().and_then(|state| state.shape);
+        if current != Some(shape) {
+            self.set_cursor_with_shape(Cursor::from_shape(shape), Some(shape));
+        }
+    }
+
+    /// The built-in shape of the cursor, or `None` if there is no cursor or it is not built-in.
+    pub fn cursor_shape(&self) -> Option<CursorShape> {
+        self.cursor.as_ref().and_then(|state| state.shape)
+    }
+
+    /// Move the hot spot of the cursor to a point of the display.
+    pub fn move_cursor(&mut self, x: isize, y: isize) {
+        let Some(state) = self.cursor.as_mut() else {
+            return;
+        };
+        state.position = (x, y);
+        let bounds = state.bounds();
+        match state.layer {
+            Some(layer) => {
+                if let Some(layer_data) = self.layers.get_mut(&layer) {
+                    layer_data.move_to(bounds.x, bounds.y);
+                }
+            }
+            None => self.driver.write().move_hardware_cursor(x, y),
+        }
+    }
+
+    /// The position of the hot spot of the cursor.
+    pub fn cursor_position(&self) -> (isize, isize) {
+        self.cursor.as_ref().map_or((0, 0), |state| state.position)
+    }
+
+    /// Show or hide the cursor.
+    pub fn show_cursor(&mut self, visible: bool) {
+        let Some(state) = self.cursor.as_mut() else {
+            return;
+        };
+        state.visible = visible;
+        match state.layer {
+            Some(layer) => {
+                if let Some(layer_data) = self.layers.get_mut(&layer) {
+                    layer_data.set_visible(visible);
+                }
+            }
+            None => {
+                let cursor = visible.then_some(&state.cursor);
+                self.driver.write().set_hardware_cursor(cursor);
+            }
+        }
+    }
+
+    fn set_cursor_with_shape(&mut self, cursor: Cursor, shape: Option<CursorShape>) {
+        let (position, visible, old_layer) = match self.cursor.take() {
+            Some(state) => (state.position, state.visible, state.layer),
+            None => ((0, 0), true, None),
+        };
+        let mut state = CursorState {
+            cursor,
+            shape,
+            position,
+            visible,
+            layer: None,
+        };
+
+        let hardware = {
+            let mut driver = self.driver.write();
+            let cursor = visible.then_some(&state.cursor);
+            let hardware = driver.set_hardware_cursor(cursor);
+            if hardware {
+                driver.move_hardware_cursor(position.0, position.1);
+            }
+            hardware
+        };
+        if let Some(old_layer) = old_layer {
+            self.remove_layer(&old_layer);
+        }
+        if !hardware {
+            let bounds = state.bounds();
+            let layer = self.create_layer(bounds.width, bounds.height, bounds.x, bounds.y);
+            if let Some(layer_data) = self.layers.get_mut(&layer) {
+                layer_data.write_pixels(0, 0, bounds.width, bounds.height, state.cursor.pixels());
+                layer_data.set_visible(visible);
+            }
+            state.layer = Some(layer);
+        }
+        self.cursor = Some(state);
+    }
+
+    /// The layer of a software cursor.
+    pub(crate) fn cursor_layer(&self) -> Option<Layer> {
+        self.cursor.as_ref().and_then(|state| state.layer)
+    }
+
+    /// Put the layer of a software cursor back on the top after the stacking order changes.
+    pub(crate) fn keep_cursor_on_top(&mut self) {
+        let Some(layer) = self.cursor_layer() else {
+            return;
+        };
+        if self.layer_sorted.last() == Some(&layer) {
+            return;
+        }
+        if let Some(priority) = self
+            .layers
+            .get(&layer)
+            .map(|layer_data| layer_data.priority)
+        {
+            self.layer_sorted.remove(priority);
+            self.layer_sorted.push(layer);
+            self.update_priorities();
+            let bounds = self.layers[&layer].bounds();
+            self.damage(bounds);
+        }
+    }
+}
+
+impl CursorState {
+    /// The area of the image on the display.
+    fn bounds(&self) -> Rect {
+        let (width, height) = self.cursor.size();
+        let (hot_x, hot_y) = self.cursor.hot_spot();
+        Rect::new(
+            self.position.0 - hot_x as isize,
+            self.position.1 - hot_y as isize,
+            width,
+            height,
+        )
+    }
+}
+
+const ARROW: [&str; 19] = [
+    "X",
+    "XX",
+    "X.X",
+    "X..X",
+    "X...X",
+    "X....X",
+    "X.....X",
+    "X......X",
+    "X.......X",
+    "X........X",
+    "X.........X",
+    "X......XXXXX",
+    "X...X..X",
+    "X..XX..X",
+    "X.X  X..X",
+    "XX   X..X",
+    "X     X..X",
+    "      X..X",
+    "       XX",
+];
+
+const I_BEAM: [&str; 16] = [
+    "XXX XXX", "X..X..X", "XXX.XXX", "  X.X", "  X.X", "  X.X", "  X.X", "  X.X", "  X.X", "  X.X",
+    "  X.X", "  X.X", "  X.X", "XXX.XXX", "X..X..X", "XXX XXX",
+];
+
+const RESIZE_HORIZONTAL: [&str; 9] = [
+    "    X       X",
+    "   XX       XX",
+    "  X.XXXXXXXXX.X",
+    " X.............X",
+    "X...............X",
+    " X.............X",
+    "  X.XXXXXXXXX.X",
+    "   XX       XX",
+    "    X       X",
+];
+
+const RESIZE_DIAGONAL: [&str; 12] = [
+    "XXXXXX",
+    "X....X",
+    "X...X",
+    "X....X",
+    "X.XX..X",
+    "XX  X..X",
+    "    X..X  XX",
+    "     X..XX.X",
+    "      X....X",
+    "       X...X",
+    "      X....X",
+    "      XXXXXX",
+];
+
+const BUSY: [&str; 16] = [
+    "XXXXXXXXXXX",
+    "X.........X",
+    " XXXXXXXXX",
+    " X.......X",
+    " X.......X",
+    "  X.....X",
+    "   X...X",
+    "    X.X",
+    "    X.X",
+    "   X...X",
+    "  X.....X",
+    " X.......X",
+    " X.......X",
+    " XXXXXXXXX",
+    "X.........X",
+    "XXXXXXXXXXX",
+];
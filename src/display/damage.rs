@@ -0,0 +1,129 @@
+use alloc::vec::Vec;
+
+/// A rectangle on the display or in a layer. \
+/// The origin can be negative, so that it can describe layers hanging off the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: isize, y: isize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// The x coordinate just after the right edge.
+    pub fn right(&self) -> isize {
+        self.x + self.width as isize
+    }
+
+    /// The y coordinate just after the bottom edge.
+    pub fn bottom(&self) -> isize {
+        self.y + self.height as isize
+    }
+}
+
+impl Rect {
+    /// Get the overlapping part of two rectangles, or `None` if they don't overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if x < right && y < bottom {
+            Some(Rect::new(x, y, (right - x) as usize, (bottom - y) as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Get the smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(x, y, (right - x) as usize, (bottom - y) as usize)
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+}
+
+/// The regions which need to be composited again. \
+/// Overlapping rectangles are merged, so that no pixel is composited twice in one flush.
+#[derive(Debug, Clone, Default)]
+pub struct Damage {
+    rects: Vec<Rect>,
+}
+
+impl Damage {
+    /// When there are more rectangles than this, they are merged into their bounding box.
+    const MAX_RECTS: usize = 16;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Mark a rectangle as damaged.
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+
+        let mut rect = rect;
+        while let Some(index) = self.rects.iter().position(|r| r.intersects(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(index));
+        }
+        self.rects.push(rect);
+
+        if self.rects.len() > Self::MAX_RECTS {
+            let bounds = self.rects.iter().fold(Rect::default(), |b, r| b.union(r));
+            self.rects.clear();
+            self.rects.push(bounds);
+        }
+    }
+
+    /// Merge all the rectangles of another damage into this one.
+    pub fn extend(&mut self, other: Damage) {
+        for rect in other.rects {
+            self.add(rect);
+        }
+    }
+
+    /// Take all the damaged rectangles, leaving this damage empty.
+    pub fn take(&mut self) -> Vec<Rect> {
+        core::mem::take(&mut self.rects)
+    }
+}
@@ -1,4 +1,10 @@
-use super::{ColorBuffer, DisplayDriver};
+use crate::{
+    color::{BlendMode, BlendSpace, Color, CompositeOp},
+    error::{clip_area, Result},
+    pixel::PixelFormat,
+};
+
+use super::{Anchor, ColorBuffer, Damage, DisplayDriver, Rect};
 
 /// This is not the real layer, but the id of the layer in the display.
 /// You can get the references of the real layer by calling function `Display::layer` and `Display::layer_mut`.
@@ -17,27 +23,42 @@ impl Layer {
 pub struct LayerData {
     width: usize,
     height: usize,
-    x: usize,
-    y: usize,
+    x: isize,
+    y: isize,
     color_buffer: ColorBuffer,
+    visible: bool,
+    composite_op: CompositeOp,
+    blend_mode: BlendMode,
+    blend_space: BlendSpace,
+    opacity: u8,
     pub(crate) priority: usize,
+    pub(crate) damage: Damage,
 }
 
 impl LayerData {
-    pub(crate) fn new(width: usize, height: usize, x: usize, y: usize, priority: usize) -> Self {
+    pub(crate) fn new(color_buffer: ColorBuffer, x: isize, y: isize, priority: usize) -> Self {
+        let (width, height) = color_buffer.size();
         Self {
             width,
             height,
             x,
             y,
-            color_buffer: ColorBuffer::new(width, height),
+            color_buffer,
+            visible: true,
+            composite_op: CompositeOp::SrcOver,
+            blend_mode: BlendMode::Normal,
+            blend_space: BlendSpace::Srgb,
+            opacity: 0xff,
             priority,
+            damage: Damage::new(),
         }
     }
 }
 
 impl LayerData {
-    pub fn position(&self) -> (usize, usize) {
+    /// The position of the top left corner on the display. \
+    /// It can be negative or out of the display, and only the part inside the display is shown.
+    pub fn position(&self) -> (isize, isize) {
         (self.x, self.y)
     }
 
@@ -45,14 +66,199 @@ impl LayerData {
         (self.width, self.height)
     }
 
-    pub fn move_to(&mut self, x: usize, y: usize) {
+    /// The format the pixels of the layer are stored in.
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.color_buffer.pixel_format()
+    }
+
+    /// The place of the layer in the stacking order. The bottom layer is 0.
+    pub fn priority(&self) -> usize {
+        self.priority
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Show or hide the layer. A hidden layer keeps its content, but is not composited.
+    pub fn set_visible(&mut self, visible: bool) {
+        if self.visible != visible {
+            self.visible = visible;
+            self.damage.add(self.bounds());
+        }
+    }
+
+    pub fn composite_op(&self) -> CompositeOp {
+        self.composite_op
+    }
+
+    /// Set how the layer is composited with the layers below it. The default one is `SrcOver`.
+    pub fn set_composite_op(&mut self, composite_op: CompositeOp) {
+        if self.composite_op != composite_op {
+            self.composite_op = composite_op;
+            self.damage.add(self.bounds());
+        }
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set how the colors of the layer are mixed with the colors below it. The default one is `Normal`.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        if self.blend_mode != blend_mode {
+            self.blend_mode = blend_mode;
+            self.damage.add(self.bounds());
+        }
+    }
+
+    pub fn blend_space(&self) -> BlendSpace {
+        self.blend_space
+    }
+
+    /// Set the space where the layer is composited with the layers below it. The default one is `Srgb`.
+    /// ## Example
+    /// ``` rust
+    /// use std::sync::Arc;
+    ///
+    /// use fur::{
+    ///     color::{BlendSpace, Color},
+    ///     display::{ColorBuffer, Display, DisplayDriver},
+    /// };
+    /// use spin::RwLock;
+    ///
+    /// let buffer = Arc::new(RwLock::new(ColorBuffer::new(1, 1)));
+    /// let mut display = Display::new(buffer.clone());
+    /// let layer = display.create_layer(1, 1, 0, 0);
+    /// display.layer_mut(&layer).unwrap().write(0, 0, 1, 1, &Color::new_argb(0x80, 0xff, 0xff, 0xff));
+    ///
+    /// // Half of the light of white is brighter than half of its sRGB value.
+    /// let mut pixels = [Color::new_rgb(0, 0, 0)];
+    /// for (blend_space, gray) in [(BlendSpace::Srgb, 0x80), (BlendSpace::Linear, 0xbc)] {
+    ///     display.layer_mut(&layer).unwrap().set_blend_space(blend_space);
+    ///     display.flush();
+    ///     buffer.read().read(0, 0, 1, 1, &mut pixels);
+    ///     assert_eq!(pixels[0].as_rgb_tuple(), (gray, gray, gray));
+    /// }
+    /// ```
+    pub fn set_blend_space(&mut self, blend_space: BlendSpace) {
+        if self.blend_space != blend_space {
+            self.blend_space = blend_space;
+            self.damage.add(self.bounds());
+        }
+    }
+
+    pub fn opacity(&self) -> u8 {
+        self.opacity
+    }
+
+    /// Set the opacity of the whole layer, which scales the alpha of every pixel. \
+    /// 0 is invisible and 0xff (the default) keeps the pixels as they are.
+    /// ## Example
+    /// ``` rust
+    /// use std::sync::Arc;
+    ///
+    /// use fur::{
+    ///     color::{BlendMode, Color},
+    ///     display::{ColorBuffer, Display, DisplayDriver},
+    /// };
+    /// use spin::RwLock;
+    ///
+    /// let buffer = Arc::new(RwLock::new(ColorBuffer::new(1, 1)));
+    /// let mut display = Display::new(buffer.clone());
+    /// let backdrop = display.create_layer(1, 1, 0, 0);
+    /// display.layer_mut(&backdrop).unwrap().write(0, 0, 1, 1, &Color::new_rgb(0x80, 0x80, 0x80));
+    /// let layer = display.create_layer(1, 1, 0, 0);
+    /// display.layer_mut(&layer).unwrap().write(0, 0, 1, 1, &Color::new_rgb(0xff, 0x80, 0));
+    ///
+    /// let mut pixels = [Color::new_rgb(0, 0, 0)];
+    /// for (blend_mode, opacity, rgb) in [
+    ///     (BlendMode::Normal, 0xff, (0xff, 0x80, 0)),
+    ///     (BlendMode::Normal, 0x80, (0xc0, 0x80, 0x40)),
+    ///     (BlendMode::Normal, 0, (0x80, 0x80, 0x80)),
+    ///     (BlendMode::Multiply, 0xff, (0x80, 0x40, 0)),
+    ///     (BlendMode::Multiply, 0x80, (0x80, 0x60, 0x40)),
+    ///     (BlendMode::Multiply, 0, (0x80, 0x80, 0x80)),
+    /// ] {
+    ///     let layer_data = display.layer_mut(&layer).unwrap();
+    ///     layer_data.set_blend_mode(blend_mode);
+    ///     layer_data.set_opacity(opacity);
+    ///     display.flush();
+    ///     buffer.read().read(0, 0, 1, 1, &mut pixels);
+    ///     assert_eq!(pixels[0].as_rgb_tuple(), rgb, "{:?} {}", blend_mode, opacity);
+    /// }
+    /// ```
+    pub fn set_opacity(&mut self, opacity: u8) {
+        if self.opacity != opacity {
+            self.opacity = opacity;
+            self.damage.add(self.bounds());
+        }
+    }
+
+    /// The area the layer covers on the display.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
+    }
+
+    pub fn move_to(&mut self, x: isize, y: isize) {
+        self.damage.add(self.bounds());
         self.x = x;
         self.y = y;
+        self.damage.add(self.bounds());
     }
 
+    /// Resize the layer, keeping its content at the top left corner. \
+    /// The new area is filled with black.
+    /// ## Panics
+    /// Panics if `try_resize` fails.
     pub fn resize(&mut self, width: usize, height: usize) {
+        self.resize_with(width, height, Anchor::TopLeft, &Color::new_rgb(0, 0, 0));
+    }
+
+    /// The same as `resize`, but it returns an error and keeps the layer as it is
+    /// if the new size is too large to allocate.
+    pub fn try_resize(&mut self, width: usize, height: usize) -> Result<()> {
+        self.try_resize_with(width, height, Anchor::TopLeft, &Color::new_rgb(0, 0, 0))
+    }
+
+    /// Resize the layer, keeping its content at `anchor` and filling the new area with `fill`. \
+    /// The position of the layer does not change.
+    /// ## Panics
+    /// Panics if `try_resize_with` fails.
+    pub fn resize_with(&mut self, width: usize, height: usize, anchor: Anchor, fill: &Color) {
+        if let Err(error) = self.try_resize_with(width, height, anchor, fill) {
+            panic!("Invalid layer: {}", error);
+        }
+    }
+
+    /// The same as `resize_with`, but it returns an error and keeps the layer as it is
+    /// if the new size is too large to allocate.
+    pub fn try_resize_with(
+        &mut self,
+        width: usize,
+        height: usize,
+        anchor: Anchor,
+        fill: &Color,
+    ) -> Result<()> {
+        self.color_buffer.try_resize(width, height, anchor, fill)?;
+        self.damage.add(self.bounds());
         self.width = width;
         self.height = height;
+        self.damage.add(self.bounds());
+        Ok(())
+    }
+}
+
+impl LayerData {
+    /// Mark the part of an area of the layer which is inside the layer.
+    fn damage_area(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        let (width, height) = clip_area(self.size(), x, y, width, height);
+        self.damage.add(Rect::new(
+            self.x + x as isize,
+            self.y + y as isize,
+            width,
+            height,
+        ));
     }
 }
 
@@ -77,6 +283,28 @@ impl DisplayDriver for LayerData {
         color: &crate::color::Color,
     ) {
         self.color_buffer.write(x, y, width, height, color);
+        self.damage_area(x, y, width, height);
+    }
+
+    fn write_pixels(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixels: &[crate::color::Color],
+    ) {
+        self.color_buffer.write_pixels(x, y, width, height, pixels);
+        self.damage_area(x, y, width, height);
+    }
+
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [crate::color::Color]) {
+        self.color_buffer.read_span(x, y, pixels);
+    }
+
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[crate::color::Color]) {
+        self.color_buffer.write_span(x, y, pixels);
+        self.damage_area(x, y, pixels.len(), 1);
     }
 
     fn size(&self) -> (usize, usize) {
@@ -1,12 +1,20 @@
 use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
 use spin::RwLock;
 
-use crate::color::Color;
+use crate::{
+    color::Color,
+    error::{check_area, check_len, clip_area, Result},
+    pixel::PixelFormat,
+};
 
 mod buffers;
+mod cursor;
+mod damage;
 mod layer;
 
 pub use buffers::*;
+pub use cursor::*;
+pub use damage::*;
 pub use layer::*;
 
 /// Implement this trait if you need to customize the action of reading and writing displays.
@@ -43,7 +51,17 @@ pub use layer::*;
 ///         }
 ///     }
 ///
-///     fn write(&mut self, x: usize, y: usize, width: usize, height: usize, pixels: &[Color]) {
+///     fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
+///         for dx in 0..width {
+///             for dy in 0..height {
+///                 let t_x = dx + x;
+///                 let t_y = dy + y;
+///                 self.data[t_y * WIDTH + t_x] = color.as_argb_u32();
+///             }
+///         }
+///     }
+///
+///     fn write_pixels(&mut self, x: usize, y: usize, width: usize, height: usize, pixels: &[Color]) {
 ///         for dx in 0..width {
 ///             for dy in 0..height {
 ///                 let t_x = dx + x;
@@ -51,16 +69,166 @@ pub use layer::*;
 ///                 self.data[t_y * WIDTH + t_x] = pixels[dy * width + dx].as_argb_u32();
 ///             }
 ///         }
-///     }    
+///     }
+///
+///     fn size(&self) -> (usize, usize) {
+///         (WIDTH, HEIGHT)
+///     }
+/// }
+///
+/// let mut buffer = DrawBuffer::new();
+/// let pixels = [Color::new_argb(0xff, 0xff, 0, 0), Color::new_argb(0xff, 0, 0xff, 0)];
+/// buffer.write_pixels(10, 10, 2, 1, &pixels);
+///
+/// let mut read_back = [Color::new_rgb(0, 0, 0), Color::new_rgb(0, 0, 0)];
+/// buffer.read(10, 10, 2, 1, &mut read_back);
+/// assert_eq!(read_back, pixels);
+/// ```
+///
+/// The drivers in Fur clip the rectangles against their size instead of panicking,
+/// and the `try_` methods report a bad rectangle as an error before touching any pixel.
+/// ``` rust
+/// use fur::color::{Color, Palette};
+/// use fur::display::{ColorBuffer, DisplayDriver, DrawBuffer, FramebufferDriver, PaletteBuffer};
+/// use fur::pixel::PixelFormat;
+///
+/// let mut framebuffer = [0; 10 * 10 * 4];
+/// let mut drivers: [Box<dyn DisplayDriver>; 4] = [
+///     Box::new(ColorBuffer::new(10, 10)),
+///     Box::new(DrawBuffer::new(10, 10, PixelFormat::Bgr888)),
+///     Box::new(FramebufferDriver::new(&mut framebuffer, 10, 10, 40, PixelFormat::Argb)),
+///     Box::new(PaletteBuffer::new(10, 10, Palette::vga())),
+/// ];
+/// let white = Color::new_rgb(0xff, 0xff, 0xff);
+/// let mut pixels = vec![Color::new_rgb(0, 0, 0); 25];
+/// for driver in drivers.iter_mut() {
+///     // Hanging off the right, the bottom, and both at once.
+///     for (x, y) in [(8, 2), (2, 8), (8, 8)] {
+///         driver.write(x, y, 5, 5, &white);
+///         driver.write_pixels(x, y, 5, 5, &pixels);
+///         driver.read(x, y, 5, 5, &mut pixels);
+///     }
+///     // Fully outside, past the right, the bottom, and the corner.
+///     for (x, y) in [(20, 9), (9, 20), (20, 20), (10, 0), (0, 10)] {
+///         driver.write(x, y, 5, 1, &white);
+///         driver.write_pixels(x, y, 5, 1, &pixels);
+///         driver.read(x, y, 5, 1, &mut pixels);
+///         assert!(driver.try_write(x, y, 5, 1, &white).is_err());
+///     }
+///
+///     driver.write(8, 0, 5, 1, &white);
+///     driver.read(7, 0, 3, 1, &mut pixels[..3]);
+///     let rgb = pixels[..3].iter().map(Color::as_rgb_tuple).collect::<Vec<_>>();
+///     assert_eq!(rgb, [(0, 0, 0), (0xff, 0xff, 0xff), (0xff, 0xff, 0xff)]);
 /// }
 /// ```
 pub trait DisplayDriver {
     /// Read pixels from (x,y) to `pixels`, and you need to tell the width and the height.
     fn read(&self, x: usize, y: usize, width: usize, height: usize, pixels: &mut [Color]);
-    /// The same as `read`, but it writes pixels.
+    /// Fill the rectangle at (x,y) with the same color.
     fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color);
+    /// The same as `read`, but it writes pixels. \
+    /// `pixels` is stored row by row, and its length is `width * height`. \
+    /// By default the rectangle is clipped against the size, and the rows are written with `write_span`.
+    fn write_pixels(&mut self, x: usize, y: usize, width: usize, height: usize, pixels: &[Color]) {
+        let (clipped_width, clipped_height) = clip_area(self.size(), x, y, width, height);
+        for dy in 0..clipped_height {
+            match pixels.get(dy * width..dy * width + clipped_width) {
+                Some(row) => self.write_span(x, y + dy, row),
+                None => break,
+            }
+        }
+    }
+    /// Read a row of pixels from (x,y) to `pixels`. The length of `pixels` is the width. \
+    /// Override it if the driver can copy a whole row at once.
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [Color]) {
+        self.read(x, y, pixels.len(), 1, pixels);
+    }
+    /// Write a row of pixels to (x,y). The length of `pixels` is the width. \
+    /// Override it if the driver can copy a whole row at once.
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[Color]) {
+        for (dx, color) in pixels.iter().enumerate() {
+            self.write(x + dx, y, 1, 1, color);
+        }
+    }
     /// Get the size of the display.
     fn size(&self) -> (usize, usize);
+
+    /// Show `cursor` as the hardware cursor, or hide it if it is `None`. \
+    /// Return `false` if the hardware can't do it, and `Display` will draw the cursor in a layer instead.
+    fn set_hardware_cursor(&mut self, cursor: Option<&Cursor>) -> bool {
+        let _ = cursor;
+        false
+    }
+    /// Move the hot spot of the hardware cursor to (x,y). It is only called after `set_hardware_cursor` succeeds.
+    fn move_hardware_cursor(&mut self, x: isize, y: isize) {
+        let _ = (x, y);
+    }
+
+    /// The same as `read`, but it fails if the rectangle is out of the display
+    /// or `pixels` is too small.
+    fn try_read(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixels: &mut [Color],
+    ) -> Result<()> {
+        check_area(self.size(), x, y, width, height)?;
+        check_len(pixels.len(), width, height)?;
+        self.read(x, y, width, height, pixels);
+        Ok(())
+    }
+    /// The same as `write`, but it fails if the rectangle is out of the display.
+    fn try_write(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        color: &Color,
+    ) -> Result<()> {
+        check_area(self.size(), x, y, width, height)?;
+        self.write(x, y, width, height, color);
+        Ok(())
+    }
+    /// The same as `write_pixels`, but it fails if the rectangle is out of the display
+    /// or `pixels` is too small.
+    fn try_write_pixels(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixels: &[Color],
+    ) -> Result<()> {
+        check_area(self.size(), x, y, width, height)?;
+        check_len(pixels.len(), width, height)?;
+        self.write_pixels(x, y, width, height, pixels);
+        Ok(())
+    }
+}
+
+/// Read a rectangle row by row with `read_span`, clipped against the size of the driver. \
+/// Drivers which can read a whole row at once can implement `read` with it.
+pub fn read_spans<D>(
+    driver: &D,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    pixels: &mut [Color],
+) where
+    D: DisplayDriver + ?Sized,
+{
+    let (clipped_width, clipped_height) = clip_area(driver.size(), x, y, width, height);
+    for dy in 0..clipped_height {
+        match pixels.get_mut(dy * width..dy * width + clipped_width) {
+            Some(row) => driver.read_span(x, y + dy, row),
+            None => break,
+        }
+    }
 }
 
 /// The main structure of FUR. \
@@ -71,7 +239,11 @@ pub struct Display {
     width: usize,
     height: usize,
     layers: BTreeMap<Layer, LayerData>,
-    layer_sorted: BTreeMap<usize, Vec<Layer>>,
+    layer_sorted: Vec<Layer>,
+    next_layer_id: usize,
+    background: Color,
+    damage: Damage,
+    cursor: Option<CursorState>,
 }
 
 impl Display {
@@ -83,24 +255,65 @@ impl Display {
             width,
             height,
             layers: BTreeMap::new(),
-            layer_sorted: BTreeMap::new(),
+            layer_sorted: Vec::new(),
+            next_layer_id: 0,
+            background: Color::new_rgb(0, 0, 0),
+            damage: Damage::new(),
+            cursor: None,
         }
     }
 }
 
+impl Display {
+    /// Set the color under all the layers. \
+    /// The whole display will be composited again on the next `flush`.
+    pub fn set_background(&mut self, color: Color) {
+        self.background = color;
+        self.damage_all();
+    }
+
+    /// Mark an area of the display, so that it will be composited again on the next `flush`.
+    pub fn damage(&mut self, rect: Rect) {
+        if let Some(rect) = rect.intersection(&self.bounds()) {
+            self.damage.add(rect);
+        }
+    }
+
+    /// Mark the whole display, so that it will be composited again on the next `flush`.
+    pub fn damage_all(&mut self) {
+        self.damage(self.bounds());
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+}
+
+/// The driver of the display may not check the bounds, so `Display` clips everything before passing it on.
 impl DisplayDriver for Display {
     fn read(&self, x: usize, y: usize, width: usize, height: usize, pixels: &mut [Color]) {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-
-        self.driver.read().read(x, y, width, height, pixels);
+        read_spans(self, x, y, width, height, pixels);
     }
 
     fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
+        let (width, height) = clip_area(self.size(), x, y, width, height);
+        if width != 0 && height != 0 {
+            self.driver.write().write(x, y, width, height, color);
+        }
+    }
 
-        self.driver.write().write(x, y, width, height, color);
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width != 0 && height != 0 {
+            self.driver.read().read_span(x, y, &mut pixels[..width]);
+        }
+    }
+
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[Color]) {
+        let (width, height) = clip_area(self.size(), x, y, pixels.len(), 1);
+        if width != 0 && height != 0 {
+            self.driver.write().write_span(x, y, &pixels[..width]);
+        }
     }
 
     fn size(&self) -> (usize, usize) {
@@ -109,19 +322,113 @@ impl DisplayDriver for Display {
 }
 
 impl Display {
-    /// Create a new layer.
-    pub fn create_layer(&mut self, width: usize, height: usize, x: usize, y: usize) -> Layer {
-        let id = self.layers.len();
+    /// Create a new layer on the top of all the other layers. \
+    /// The position can be negative or out of the display, the layer is clipped when composited.
+    pub fn create_layer(&mut self, width: usize, height: usize, x: isize, y: isize) -> Layer {
+        self.create_layer_with_format(width, height, x, y, PixelFormat::Argb)
+    }
+
+    /// The same as `create_layer`, but the pixels of the layer are stored in `pixel_format`. \
+    /// A layer in a format without alpha is always opaque.
+    /// ## Panics
+    /// Panics if `try_create_layer_with_format` fails.
+    pub fn create_layer_with_format(
+        &mut self,
+        width: usize,
+        height: usize,
+        x: isize,
+        y: isize,
+        pixel_format: PixelFormat,
+    ) -> Layer {
+        match self.try_create_layer_with_format(width, height, x, y, pixel_format) {
+            Ok(layer) => layer,
+            Err(error) => panic!("Invalid layer: {}", error),
+        }
+    }
+
+    /// The same as `create_layer_with_format`, but it returns an error if the pixel format is not supported,
+    /// such as `U8`, or the layer is too large to allocate, and no layer is created.
+    /// ## Example
+    /// ``` rust
+    /// use std::sync::Arc;
+    ///
+    /// use fur::display::{Display, DrawBuffer};
+    /// use fur::pixel::PixelFormat;
+    /// use fur::Error;
+    /// use spin::RwLock;
+    ///
+    /// let buffer = Arc::new(RwLock::new(DrawBuffer::new(64, 64, PixelFormat::Argb)));
+    /// let mut display = Display::new(buffer);
+    /// assert!(display.try_create_layer_with_format(8, 8, 0, 0, PixelFormat::Rgb565).is_ok());
+    /// assert_eq!(
+    ///     display.try_create_layer_with_format(8, 8, 0, 0, PixelFormat::U8),
+    ///     Err(Error::UnsupportedFormat(PixelFormat::U8)),
+    /// );
+    /// assert_eq!(
+    ///     display.try_create_layer_with_format(usize::MAX / 2, 3, 0, 0, PixelFormat::Argb),
+    ///     Err(Error::InvalidSize { width: usize::MAX / 2, height: 3 }),
+    /// );
+    /// assert_eq!(display.paint_order().count(), 1);
+    /// ```
+    pub fn try_create_layer_with_format(
+        &mut self,
+        width: usize,
+        height: usize,
+        x: isize,
+        y: isize,
+        pixel_format: PixelFormat,
+    ) -> Result<Layer> {
+        let color_buffer = ColorBuffer::try_with_format(width, height, pixel_format)?;
+        let id = self.next_layer_id;
+        self.next_layer_id += 1;
         let layer = Layer::new(id);
+        let priority = self.layer_sorted.len();
         self.layers
-            .insert(layer, LayerData::new(width, height, x, y, id));
-        if let Some(layers) = self.layer_sorted.get_mut(&id) {
-            layers.push(layer);
-        } else {
-            self.layer_sorted.insert(id, alloc::vec![layer]);
+            .insert(layer, LayerData::new(color_buffer, x, y, priority));
+        self.layer_sorted.push(layer);
+        self.damage(Rect::new(x, y, width, height));
+        self.keep_cursor_on_top();
+
+        Ok(layer)
+    }
+
+    /// Destroy a layer, and return its data if it exists. \
+    /// The id of the layer will never be used again. \
+    /// The layer of a software cursor belongs to the cursor and is not removed.
+    /// ## Example
+    /// ``` rust
+    /// use std::sync::Arc;
+    ///
+    /// use fur::display::{CursorShape, Display, DrawBuffer};
+    /// use fur::pixel::PixelFormat;
+    /// use spin::RwLock;
+    ///
+    /// let buffer = Arc::new(RwLock::new(DrawBuffer::new(64, 64, PixelFormat::Argb)));
+    /// let mut display = Display::new(buffer);
+    /// let layer = display.create_layer(8, 8, 0, 0);
+    /// display.set_cursor_shape(CursorShape::Arrow);
+    ///
+    /// // A `DrawBuffer` has no hardware cursor, so the cursor is drawn in the top layer.
+    /// let cursor = *display.paint_order().last().unwrap();
+    /// assert_ne!(cursor, layer);
+    /// assert!(display.remove_layer(&cursor).is_none());
+    ///
+    /// display.move_cursor(20, 30);
+    /// assert_eq!(display.layer(&cursor).unwrap().position(), (20, 30));
+    /// assert!(display.remove_layer(&layer).is_some());
+    /// ```
+    pub fn remove_layer(&mut self, layer: &Layer) -> Option<LayerData> {
+        if self.cursor_layer() == Some(*layer) {
+            return None;
         }
+        let mut layer_data = self.layers.remove(layer)?;
+        self.layer_sorted.remove(layer_data.priority);
+        self.update_priorities();
 
-        layer
+        let damage = core::mem::take(&mut layer_data.damage);
+        self.damage.extend(damage);
+        self.damage(layer_data.bounds());
+        Some(layer_data)
     }
 
     /// Get the immutable reference to the layer's data.
@@ -136,81 +443,222 @@ impl Display {
     }
 }
 
+/// Layers are painted from the bottom to the top. \
+/// Every layer has its own priority, the bottom one is 0 and the top one is `len - 1`,
+/// so two layers are never painted in an unspecified order.
 impl Display {
+    /// Put `layer` just above `other`.
     pub fn put_upper_than(&mut self, layer: &Layer, other: &Layer) -> Option<()> {
-        let old_priority = self.layer(layer)?.priority;
-        let new_priority = self.layer(other)?.priority + 1;
-        self.layer_sorted
-            .get_mut(&old_priority)
-            .unwrap()
-            .retain(|l| l != layer);
-        if let Some(layers) = self.layer_sorted.get_mut(&new_priority) {
-            layers.push(*layer);
-        } else {
-            self.layer_sorted.insert(new_priority, alloc::vec![*layer]);
-        }
-        self.layer_mut(layer)?.priority = new_priority;
+        self.layer(other)?;
+        if layer == other {
+            return Some(());
+        }
+        self.detach(layer)?;
+        let priority = self.layer(other)?.priority + 1;
+        self.attach(layer, priority);
         Some(())
     }
 
-    pub fn flush_all(&self) {
-        for (_, layers) in self.layer_sorted.iter() {
-            for layer in layers {
-                let layer_data = self.layer(layer).unwrap();
-                let (x, y) = layer_data.position();
-                let (width, height) = layer_data.size();
+    /// Put `layer` just below `other`.
+    pub fn put_lower_than(&mut self, layer: &Layer, other: &Layer) -> Option<()> {
+        self.layer(other)?;
+        if layer == other {
+            return Some(());
+        }
+        self.detach(layer)?;
+        let priority = self.layer(other)?.priority;
+        self.attach(layer, priority);
+        Some(())
+    }
 
-                for dx in 0..width {
-                    for dy in 0..height {
-                        let t_x = dx + x;
-                        let t_y = dy + y;
+    /// Put `layer` above all the other layers.
+    pub fn raise_to_top(&mut self, layer: &Layer) -> Option<()> {
+        self.detach(layer)?;
+        self.attach(layer, self.layer_sorted.len());
+        Some(())
+    }
 
-                        let mut color = [Color::new_rgb(0, 0, 0)];
-                        layer_data.read(dx, dy, 1, 1, &mut color);
-                        let color = color[0].clone();
+    /// Put `layer` below all the other layers.
+    pub fn lower_to_bottom(&mut self, layer: &Layer) -> Option<()> {
+        self.detach(layer)?;
+        self.attach(layer, 0);
+        Some(())
+    }
 
-                        let mut base_color = [Color::new_rgb(0, 0, 0)];
-                        self.driver.read().read(t_x, t_y, 1, 1, &mut base_color);
-                        let base_color = base_color[0].clone();
+    /// Exchange the places of two layers in the stacking order.
+    pub fn swap_order(&mut self, layer: &Layer, other: &Layer) -> Option<()> {
+        let priority = self.layer(layer)?.priority;
+        let other_priority = self.layer(other)?.priority;
+        self.layer_sorted.swap(priority, other_priority);
+        self.update_priorities();
 
-                        let color = base_color.mix(&color);
-                        self.driver.write().write(t_x, t_y, 1, 1, &color);
-                    }
-                }
+        let bounds = self.layer(layer)?.bounds();
+        let other_bounds = self.layer(other)?.bounds();
+        self.damage(bounds);
+        self.damage(other_bounds);
+        self.keep_cursor_on_top();
+        Some(())
+    }
+
+    /// Iterate over the layers in paint order, from the bottom to the top. \
+    /// Call `rev` on it to go from the top to the bottom.
+    pub fn paint_order(&self) -> impl DoubleEndedIterator<Item = &Layer> + '_ {
+        self.layer_sorted.iter()
+    }
+
+    /// Find the top visible layer at a point of the display. The layer of the cursor is skipped.
+    pub fn layer_at(&self, x: isize, y: isize) -> Option<Layer> {
+        let cursor_layer = self.cursor_layer();
+        self.paint_order()
+            .rev()
+            .filter(|layer| Some(**layer) != cursor_layer)
+            .find(|layer| {
+                self.layer(layer).map_or(false, |layer_data| {
+                    layer_data.is_visible() && layer_data.bounds().contains(x, y)
+                })
+            })
+            .copied()
+    }
+
+    /// Take the layer out of the stacking order, and return its old priority.
+    fn detach(&mut self, layer: &Layer) -> Option<usize> {
+        let priority = self.layer(layer)?.priority;
+        self.layer_sorted.remove(priority);
+        self.update_priorities();
+        Some(priority)
+    }
+
+    /// Put a detached layer back into the stacking order at `priority`.
+    fn attach(&mut self, layer: &Layer, priority: usize) {
+        self.layer_sorted.insert(priority, *layer);
+        self.update_priorities();
+        if let Some(layer_data) = self.layer(layer) {
+            let bounds = layer_data.bounds();
+            self.damage(bounds);
+        }
+        self.keep_cursor_on_top();
+    }
+
+    fn update_priorities(&mut self) {
+        for (priority, layer) in self.layer_sorted.iter().enumerate() {
+            if let Some(layer_data) = self.layers.get_mut(layer) {
+                layer_data.priority = priority;
             }
         }
     }
+}
 
+impl Display {
+    /// Composite all the damaged areas again and write them to the driver. \
+    /// Areas nobody has touched since the last flush are left alone.
+    /// Layers hanging off the display are clipped against its edges.
+    /// ## Example
+    /// ``` rust
+    /// use std::sync::Arc;
+    ///
+    /// use fur::{
+    ///     color::Color,
+    ///     display::{ColorBuffer, Display, DisplayDriver},
+    /// };
+    /// use spin::RwLock;
+    ///
+    /// let buffer = Arc::new(RwLock::new(ColorBuffer::new(8, 8)));
+    /// let mut display = Display::new(buffer.clone());
+    /// let white = Color::new_rgb(0xff, 0xff, 0xff);
+    ///
+    /// // Each layer hangs off one edge, and only a 1 pixel wide strip is visible.
+    /// for (x, y) in [(-3, 2), (2, -3), (7, 2), (2, 7)] {
+    ///     let layer = display.create_layer(4, 4, x, y);
+    ///     display.layer_mut(&layer).unwrap().write(0, 0, 4, 4, &white);
+    /// }
+    /// display.flush();
+    ///
+    /// let mut pixels = [Color::new_rgb(0, 0, 0); 1];
+    /// for (x, y, visible) in [(0, 3, true), (1, 3, false), (3, 0, true), (3, 1, false),
+    ///                         (7, 3, true), (6, 3, false), (3, 7, true), (3, 6, false)] {
+    ///     buffer.read().read(x, y, 1, 1, &mut pixels);
+    ///     assert_eq!(pixels[0] == white, visible);
+    /// }
+    /// ```
+    pub fn flush(&mut self) {
+        for layer_data in self.layers.values_mut() {
+            let damage = core::mem::take(&mut layer_data.damage);
+            self.damage.extend(damage);
+        }
+
+        let screen = self.bounds();
+        for rect in self.damage.take() {
+            if let Some(rect) = rect.intersection(&screen) {
+                self.composite(&rect);
+            }
+        }
+    }
+
+    /// Composite the whole display again.
+    pub fn flush_all(&self) {
+        self.composite(&self.bounds());
+    }
+
+    /// Composite the area `x_range.0..x_range.1`, `y_range.0..y_range.1` again.
     pub fn flush_area(&self, x_range: (usize, usize), y_range: (usize, usize)) {
-        for (_, layers) in self.layer_sorted.iter() {
-            for layer in layers {
+        let rect = Rect::new(
+            x_range.0 as isize,
+            y_range.0 as isize,
+            x_range.1.saturating_sub(x_range.0),
+            y_range.1.saturating_sub(y_range.0),
+        );
+        if let Some(rect) = rect.intersection(&self.bounds()) {
+            self.composite(&rect);
+        }
+    }
+
+    /// Blend every layer overlapping `rect` on top of the background, from the bottom to the top,
+    /// and write the result to the driver. `rect` must be inside the display.
+    fn composite(&self, rect: &Rect) {
+        let mut driver = self.driver.write();
+        let mut row = alloc::vec![Color::new_rgb(0, 0, 0); rect.width];
+        let mut layer_row = alloc::vec![Color::new_rgb(0, 0, 0); rect.width];
+
+        for y in rect.y..rect.bottom() {
+            row.fill(self.background.clone());
+
+            for layer in self.layer_sorted.iter() {
                 let layer_data = self.layer(layer).unwrap();
-                let (x, y) = layer_data.position();
-                let (width, height) = layer_data.size();
-                if x >= x_range.0
-                    && (x + width) >= x_range.0
-                    && y >= y_range.0
-                    && (y + height) >= y_range.0
-                {
-                    for dx in 0..width {
-                        for dy in 0..height {
-                            let t_x = dx + x;
-                            let t_y = dy + y;
-
-                            let mut color = [Color::new_rgb(0, 0, 0)];
-                            layer_data.read(dx, dy, 1, 1, &mut color);
-                            let color = color[0].clone();
-
-                            let mut base_color = [Color::new_rgb(0, 0, 0)];
-                            self.driver.read().read(t_x, t_y, 1, 1, &mut base_color);
-                            let base_color = base_color[0].clone();
-
-                            let color = base_color.mix(&color);
-                            self.driver.write().write(t_x, t_y, 1, 1, &color);
-                        }
-                    }
+                if !layer_data.is_visible() {
+                    continue;
+                }
+                let bounds = layer_data.bounds();
+                let area = match bounds.intersection(&Rect::new(rect.x, y, rect.width, 1)) {
+                    Some(area) => area,
+                    None => continue,
+                };
+
+                let pixels = &mut layer_row[..area.width];
+                layer_data.read_span(
+                    (area.x - bounds.x) as usize,
+                    (y - bounds.y) as usize,
+                    pixels,
+                );
+                let start = (area.x - rect.x) as usize;
+                let composite_op = layer_data.composite_op();
+                let blend_mode = layer_data.blend_mode();
+                let blend_space = layer_data.blend_space();
+                let opacity = layer_data.opacity();
+                for (base_color, color) in row[start..start + area.width].iter_mut().zip(pixels) {
+                    *base_color = if opacity == 0xff {
+                        color.blend_in(base_color, blend_mode, composite_op, blend_space)
+                    } else {
+                        color.fade(opacity).blend_in(
+                            base_color,
+                            blend_mode,
+                            composite_op,
+                            blend_space,
+                        )
+                    };
                 }
             }
+
+            driver.write_span(rect.x as usize, y as usize, &row);
         }
     }
 }
@@ -0,0 +1,123 @@
+use core::fmt;
+
+use crate::pixel::PixelFormat;
+
+/// The errors returned by the fallible APIs of Fur. \
+/// They never bring down the caller, so that a bad request from a client can be rejected safely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The rectangle is not fully inside the target, whose size is `bounds`.
+    OutOfBounds {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        bounds: (usize, usize),
+    },
+    /// The pixel slice or the framebuffer is shorter than it needs to be.
+    BufferTooSmall { required: usize, actual: usize },
+    /// The pitch of a framebuffer is shorter than a row of `row` bytes.
+    InvalidPitch { pitch: usize, row: usize },
+    /// The size is empty, or the number of bytes it needs doesn't fit in `usize`.
+    InvalidSize { width: usize, height: usize },
+    /// The color or the pixel cannot be converted with this pixel format.
+    UnsupportedFormat(PixelFormat),
+    /// The text cannot be parsed as a color.
+    InvalidColor,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds {
+                x,
+                y,
+                width,
+                height,
+                bounds,
+            } => write!(
+                f,
+                "rectangle {}x{} at ({}, {}) is out of {}x{}",
+                width, height, x, y, bounds.0, bounds.1
+            ),
+            Self::BufferTooSmall { required, actual } => write!(
+                f,
+                "buffer holds {} elements, but {} are required",
+                actual, required
+            ),
+            Self::InvalidPitch { pitch, row } => write!(
+                f,
+                "pitch of {} bytes is shorter than a row of {} bytes",
+                pitch, row
+            ),
+            Self::InvalidSize { width, height } => write!(f, "invalid size {}x{}", width, height),
+            Self::UnsupportedFormat(format) => {
+                write!(f, "conversion is not supported by {:?}", format)
+            }
+            Self::InvalidColor => write!(f, "invalid color"),
+        }
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Check that the rectangle is fully inside an area of `size`.
+pub(crate) fn check_area(
+    size: (usize, usize),
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> Result<()> {
+    let inside = x.checked_add(width).map_or(false, |right| right <= size.0)
+        && y.checked_add(height)
+            .map_or(false, |bottom| bottom <= size.1);
+    if inside {
+        Ok(())
+    } else {
+        Err(Error::OutOfBounds {
+            x,
+            y,
+            width,
+            height,
+            bounds: size,
+        })
+    }
+}
+
+/// Check that a pixel slice can hold `width * height` pixels.
+pub(crate) fn check_len(len: usize, width: usize, height: usize) -> Result<()> {
+    let required = width.saturating_mul(height);
+    if len >= required {
+        Ok(())
+    } else {
+        Err(Error::BufferTooSmall {
+            required,
+            actual: len,
+        })
+    }
+}
+
+/// Get the number of bytes of a buffer of `width * height` pixels of `pixel_size` bytes,
+/// or an error if it doesn't fit in `usize` or is too large to allocate.
+pub(crate) fn buffer_len(width: usize, height: usize, pixel_size: usize) -> Result<usize> {
+    width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(pixel_size))
+        .filter(|&len| len <= isize::MAX as usize)
+        .ok_or(Error::InvalidSize { width, height })
+}
+
+/// Clip the rectangle against an area of `size`, and return the width and height left.
+pub(crate) fn clip_area(
+    size: (usize, usize),
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> (usize, usize) {
+    (
+        width.min(size.0.saturating_sub(x)),
+        height.min(size.1.saturating_sub(y)),
+    )
+}
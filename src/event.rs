@@ -0,0 +1,149 @@
+/// The state of the modifier keys when an event happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub meta: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+    /// Any other button, numbered by the driver.
+    Other(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerAction {
+    Move,
+    ButtonDown(PointerButton),
+    ButtonUp(PointerButton),
+    /// Scroll by some steps. Positive values go right and down.
+    Scroll {
+        dx: isize,
+        dy: isize,
+    },
+}
+
+/// An event of the mouse, the touchpad or anything like them. \
+/// The position is on the display when the event is dispatched,
+/// and on the window when the window receives it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerEvent {
+    pub x: isize,
+    pub y: isize,
+    pub action: PointerAction,
+    pub modifiers: Modifiers,
+}
+
+impl PointerEvent {
+    pub fn new(x: isize, y: isize, action: PointerAction) -> Self {
+        Self {
+            x,
+            y,
+            action,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// The same event at another position.
+    pub fn moved_to(&self, x: isize, y: isize) -> Self {
+        Self { x, y, ..*self }
+    }
+}
+
+/// The meaning of a key, which doesn't depend on the keyboard layout except for `Character`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCode {
+    /// A key which types a character, such as a letter, a digit or a symbol. Letters are lowercase.
+    Character(char),
+    Space,
+    Enter,
+    Escape,
+    Backspace,
+    Tab,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// F1 to F24.
+    Function(u8),
+    Shift,
+    Control,
+    Alt,
+    Meta,
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    PrintScreen,
+    Pause,
+    Menu,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    Pressed,
+    Released,
+    /// The key is held down and the keyboard repeats it.
+    Repeated,
+}
+
+/// An event of the keyboard. It is sent to the focused window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// The raw code from the keyboard, such as a PS/2 scancode.
+    pub scancode: u16,
+    pub keycode: KeyCode,
+    pub state: KeyState,
+    pub modifiers: Modifiers,
+    /// The character typed by the key with the modifiers, if any.
+    pub text: Option<char>,
+}
+
+impl KeyEvent {
+    pub fn new(scancode: u16, keycode: KeyCode, state: KeyState) -> Self {
+        Self {
+            scancode,
+            keycode,
+            state,
+            modifiers: Modifiers::default(),
+            text: None,
+        }
+    }
+}
+
+/// An input event. \
+/// Drivers of the mouse and the keyboard turn what they receive into events,
+/// and `WindowManager::dispatch` sends them to the right window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Pointer(PointerEvent),
+    Key(KeyEvent),
+    /// The window gets the focus, so keyboard events will be sent to it.
+    FocusIn,
+    /// The window loses the focus.
+    FocusOut,
+    /// The pointer moves into the window.
+    PointerEnter,
+    /// The pointer moves out of the window.
+    PointerLeave,
+    /// The window has been resized and drawn again, so the client area has to be drawn again.
+    Resized {
+        width: usize,
+        height: usize,
+    },
+    /// The close button has been clicked. The window is not closed until it is destroyed.
+    CloseRequested,
+}
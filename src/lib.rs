@@ -9,6 +9,11 @@ extern crate alloc;
 
 pub mod color;
 pub mod display;
+pub mod error;
+pub mod event;
 pub mod pixel;
 pub mod style;
+pub mod widget;
 pub mod window;
+
+pub use error::{Error, Result};
@@ -1,11 +1,28 @@
-use crate::color::Color;
+use crate::{
+    color::Color,
+    error::{Error, Result},
+};
 
+/// The layout of a pixel. \
+/// The names of the 32-bit and 16-bit formats describe the pixel as a number from the high bits to the low bits,
+/// and the pixel is stored in native endian.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PixelFormat {
     ZeroRgb,
     RgbZero,
     Argb,
     Bgra,
+    BgrZero,
+    Abgr,
+    /// 24-bit pixels, stored as 3 bytes in the order of blue, green and red.
+    Bgr888,
+    /// 16-bit pixels with 5 bits red, 6 bits green and 5 bits blue.
+    Rgb565,
+    /// 16-bit pixels with 5 bits for each channel, and the highest bit unused.
+    Rgb555,
+    /// 8-bit grayscale.
+    Gray8,
+    /// 8-bit indices of a palette.
     U8,
 }
 
@@ -16,18 +33,33 @@ impl PixelFormat {
             Self::RgbZero => 4,
             Self::Argb => 4,
             Self::Bgra => 4,
+            Self::BgrZero => 4,
+            Self::Abgr => 4,
+            Self::Bgr888 => 3,
+            Self::Rgb565 => 2,
+            Self::Rgb555 => 2,
+            Self::Gray8 => 1,
             Self::U8 => 1,
         }
     }
 }
 
 impl PixelFormat {
+    /// Convert a color into a pixel.
+    /// ## Panics
+    /// Panics if the format is `U8` but the color is not. Use `try_color_as_u32` to get an error instead.
     pub fn color_as_u32(&self, color: &Color) -> u32 {
         match self {
             Self::ZeroRgb => color.as_0rgb_u32(),
             Self::RgbZero => color.as_rgb0_u32(),
             Self::Argb => color.as_argb_u32(),
             Self::Bgra => color.as_bgra_u32(),
+            Self::BgrZero => color.as_bgr0_u32(),
+            Self::Abgr => color.as_abgr_u32(),
+            Self::Bgr888 => color.as_0rgb_u32(),
+            Self::Rgb565 => color.as_rgb565_u16() as u32,
+            Self::Rgb555 => color.as_rgb555_u16() as u32,
+            Self::Gray8 => color.as_gray_u8() as u32,
             Self::U8 => match color {
                 Color::U8(_, color) => *color as u32,
                 _ => panic!("Only U8 colors can be converted to U8 pixels"),
@@ -37,13 +69,69 @@ impl PixelFormat {
 }
 
 impl PixelFormat {
+    /// Convert a pixel into a color.
+    /// ## Panics
+    /// Panics if the format is `U8`, because there is no palette. Use `try_u32_as_color` to get an error instead.
     pub fn u32_as_color(&self, color: u32) -> Color {
         match self {
             Self::ZeroRgb => Color::from_0rgb_u32(color),
             Self::RgbZero => Color::from_rgb0_u32(color),
             Self::Argb => Color::from_argb_u32(color),
             Self::Bgra => Color::from_bgra_u32(color),
+            Self::BgrZero => Color::from_bgr0_u32(color),
+            Self::Abgr => Color::from_abgr_u32(color),
+            Self::Bgr888 => Color::from_0rgb_u32(color),
+            Self::Rgb565 => Color::from_rgb565_u16(color as u16),
+            Self::Rgb555 => Color::from_rgb555_u16(color as u16),
+            Self::Gray8 => Color::from_gray_u8(color as u8),
             Self::U8 => panic!("U32 cannot be converted to U8 color."),
         }
     }
 }
+
+impl PixelFormat {
+    /// The same as `color_as_u32`, but it fails instead of panicking.
+    pub fn try_color_as_u32(&self, color: &Color) -> Result<u32> {
+        match (self, color) {
+            (Self::U8, Color::U8(_, color)) => Ok(*color as u32),
+            (Self::U8, _) => Err(Error::UnsupportedFormat(*self)),
+            _ => Ok(self.color_as_u32(color)),
+        }
+    }
+
+    /// The same as `u32_as_color`, but it fails instead of panicking.
+    pub fn try_u32_as_color(&self, color: u32) -> Result<Color> {
+        match self {
+            Self::U8 => Err(Error::UnsupportedFormat(*self)),
+            _ => Ok(self.u32_as_color(color)),
+        }
+    }
+}
+
+impl PixelFormat {
+    /// Write the pixel of `color` into `bytes`, whose length is `self.size()`.
+    /// ## Panics
+    /// Panics in the same cases as `color_as_u32`.
+    pub fn encode(&self, color: &Color, bytes: &mut [u8]) {
+        let pixel = self.color_as_u32(color);
+        match self.size() {
+            1 => bytes[0] = pixel as u8,
+            2 => bytes.copy_from_slice(&(pixel as u16).to_ne_bytes()),
+            3 => bytes.copy_from_slice(&pixel.to_le_bytes()[..3]),
+            _ => bytes.copy_from_slice(&pixel.to_ne_bytes()),
+        }
+    }
+
+    /// Read the pixel in `bytes`, whose length is `self.size()`, as a color.
+    /// ## Panics
+    /// Panics in the same cases as `u32_as_color`.
+    pub fn decode(&self, bytes: &[u8]) -> Color {
+        let pixel = match self.size() {
+            1 => bytes[0] as u32,
+            2 => u16::from_ne_bytes([bytes[0], bytes[1]]) as u32,
+            3 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]),
+            _ => u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        };
+        self.u32_as_color(pixel)
+    }
+}
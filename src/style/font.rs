@@ -0,0 +1,144 @@
+use alloc::{string::String, sync::Arc, vec::Vec};
+use cosmic_text::{fontdb::Source, FontSystem};
+use spin::{Lazy, Mutex, MutexGuard};
+
+use super::GlyphCache;
+
+pub use cosmic_text::{FamilyOwned as FontFamily, Style as FontStyle, Weight as FontWeight};
+
+static GLOBAL_REGISTRY: Lazy<FontRegistry> = Lazy::new(FontRegistry::new);
+
+pub(crate) struct Fonts {
+    pub(crate) font_system: FontSystem,
+    pub(crate) glyph_cache: GlyphCache,
+}
+
+/// The fonts which can be used to draw text. \
+/// There is no font directory to scan without `std`, so fonts have to be loaded from memory,
+/// usually from blobs embedded with `include_bytes!`. \
+/// Cloning the registry is cheap, and all the clones share the same fonts and glyph cache.
+/// ## Example
+/// ``` rust
+/// use fur::color::Color;
+/// use fur::display::{ColorBuffer, DisplayDriver};
+/// use fur::style::{FontRegistry, TextBuilder};
+///
+/// let fonts = FontRegistry::global();
+/// assert_eq!(fonts.load_font(include_bytes!("../../assets/fonts/FurBlocks.ttf")), 1);
+/// assert!(fonts.families().contains(&String::from("Fur Blocks")));
+///
+/// let mut driver = ColorBuffer::new(64, 16);
+/// TextBuilder::default().text("Hello").draw(&mut driver);
+/// let mut pixels = vec![Color::new_rgb(0, 0, 0); 64 * 16];
+/// driver.read(0, 0, 64, 16, &mut pixels);
+/// assert!(pixels.iter().any(|pixel| pixel.as_rgb_tuple() != (0, 0, 0)));
+/// ```
+#[derive(Clone)]
+pub struct FontRegistry {
+    fonts: Arc<Mutex<Fonts>>,
+}
+
+impl Default for FontRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FontRegistry {
+    /// Create an empty registry which is not shared with the global one.
+    pub fn new() -> Self {
+        Self {
+            fonts: Arc::new(Mutex::new(Fonts {
+                font_system: FontSystem::new(),
+                glyph_cache: GlyphCache::new(),
+            })),
+        }
+    }
+
+    /// Get the registry used by `TextBuilder::default`.
+    pub fn global() -> Self {
+        GLOBAL_REGISTRY.clone()
+    }
+}
+
+impl FontRegistry {
+    /// Load a TTF/OTF file or a font collection. \
+    /// Returns how many faces were found in the data.
+    pub fn load_font(&self, data: &'static [u8]) -> usize {
+        self.lock()
+            .font_system
+            .db_mut()
+            .load_font_source(Source::Binary(Arc::new(data)))
+            .len()
+    }
+
+    /// Set the family used for `FontFamily::SansSerif`, which is the default family of `TextBuilder`.
+    pub fn set_sans_serif_family<S>(&self, family: S) -> &Self
+    where
+        String: From<S>,
+    {
+        self.lock()
+            .font_system
+            .db_mut()
+            .set_sans_serif_family(String::from(family));
+        self
+    }
+
+    /// Set the family used for `FontFamily::Serif`.
+    pub fn set_serif_family<S>(&self, family: S) -> &Self
+    where
+        String: From<S>,
+    {
+        self.lock()
+            .font_system
+            .db_mut()
+            .set_serif_family(String::from(family));
+        self
+    }
+
+    /// Set the family used for `FontFamily::Monospace`.
+    pub fn set_monospace_family<S>(&self, family: S) -> &Self
+    where
+        String: From<S>,
+    {
+        self.lock()
+            .font_system
+            .db_mut()
+            .set_monospace_family(String::from(family));
+        self
+    }
+
+    /// Get the names of all the loaded families.
+    pub fn families(&self) -> Vec<String> {
+        let fonts = self.lock();
+        let mut families = fonts
+            .font_system
+            .db()
+            .faces()
+            .flat_map(|face| face.families.iter().map(|(name, _)| name.clone()))
+            .collect::<Vec<_>>();
+        families.sort();
+        families.dedup();
+        families
+    }
+}
+
+impl FontRegistry {
+    /// Set how many rasterized glyphs are kept, which is `GlyphCache::DEFAULT_CAPACITY` by default.
+    pub fn set_glyph_cache_capacity(&self, capacity: usize) -> &Self {
+        self.lock().glyph_cache.set_capacity(capacity);
+        self
+    }
+
+    /// Drop all the rasterized glyphs, to free their memory.
+    pub fn clear_glyph_cache(&self) -> &Self {
+        self.lock().glyph_cache.clear();
+        self
+    }
+}
+
+impl FontRegistry {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Fonts> {
+        self.fonts.lock()
+    }
+}
@@ -0,0 +1,177 @@
+use alloc::collections::BTreeMap;
+use cosmic_text::{CacheKey, CacheKeyFlags, FontSystem};
+use swash::{
+    scale::{image::Image, Render, ScaleContext, Source, StrikeWith},
+    zeno::{Angle, Format, Transform, Vector},
+    FontRef,
+};
+
+/// Rasterizes glyphs with swash and keeps the results. \
+/// It does the same thing as `cosmic_text::SwashCache`, which is not available without `std`. \
+/// At most `capacity` glyphs are kept, and the one used least recently is dropped to make room for a new one.
+/// ## Example
+/// ``` rust
+/// use cosmic_text::{CacheKey, CacheKeyFlags, FontSystem};
+/// use fur::style::GlyphCache;
+///
+/// let mut font_system = FontSystem::new();
+/// let data = include_bytes!("../../assets/fonts/FurBlocks.ttf");
+/// font_system.db_mut().load_font_data(data.to_vec());
+/// let font_id = font_system.db().faces().next().unwrap().id;
+/// let key = |font_size| {
+///     // Glyph 2 is the box of Fur Blocks, 7/10 of the font size high.
+///     CacheKey::new(font_id, 2, font_size, (0.0, 0.0), CacheKeyFlags::empty()).0
+/// };
+///
+/// let mut cache = GlyphCache::with_capacity(2);
+/// let image = cache.image(&mut font_system, key(10.0)).as_ref().unwrap();
+/// assert_eq!(image.placement.height, 7);
+/// assert!(image.data.contains(&0xff));
+/// let image = cache.image(&mut font_system, key(20.0)).as_ref().unwrap();
+/// assert_eq!(image.placement.height, 14);
+///
+/// // Using the first glyph again makes the second one the least recently used.
+/// cache.image(&mut font_system, key(10.0));
+/// cache.image(&mut font_system, key(30.0));
+/// assert_eq!(cache.len(), 2);
+/// assert!(cache.contains(&key(10.0)) && cache.contains(&key(30.0)));
+/// assert!(!cache.contains(&key(20.0)));
+///
+/// cache.set_capacity(1);
+/// assert!(cache.contains(&key(30.0)) && !cache.contains(&key(10.0)));
+/// cache.clear();
+/// assert!(cache.is_empty());
+/// ```
+pub struct GlyphCache {
+    context: ScaleContext,
+    /// The images, with the time they were used last.
+    images: BTreeMap<CacheKey, (Option<Image>, u64)>,
+    /// The glyphs by the time they were used last, from the least recently used.
+    recency: BTreeMap<u64, CacheKey>,
+    capacity: usize,
+    time: u64,
+}
+
+impl Default for GlyphCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GlyphCache {
+    /// The number of glyphs kept by `GlyphCache::new`.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    /// Create a cache which keeps at most `capacity` glyphs. A capacity of 0 is treated as 1.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            context: ScaleContext::new(),
+            images: BTreeMap::new(),
+            recency: BTreeMap::new(),
+            capacity: capacity.max(1),
+            time: 0,
+        }
+    }
+}
+
+impl GlyphCache {
+    /// Get the rasterized image of a glyph, rendering it if it is not cached yet.
+    pub fn image(&mut self, font_system: &mut FontSystem, cache_key: CacheKey) -> &Option<Image> {
+        self.time += 1;
+        let time = self.time;
+        match self.images.get_mut(&cache_key) {
+            Some(entry) => {
+                self.recency.remove(&entry.1);
+                entry.1 = time;
+            }
+            None => {
+                self.shrink_to(self.capacity - 1);
+                let image = render_glyph(font_system, &mut self.context, cache_key);
+                self.images.insert(cache_key, (image, time));
+            }
+        }
+        self.recency.insert(time, cache_key);
+        &self.images[&cache_key].0
+    }
+
+    /// Check whether the image of a glyph is cached.
+    pub fn contains(&self, cache_key: &CacheKey) -> bool {
+        self.images.contains_key(cache_key)
+    }
+
+    /// Set how many glyphs are kept, dropping the ones used least recently if there are more.
+    pub fn set_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.capacity = capacity.max(1);
+        self.shrink_to(self.capacity);
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of cached glyphs.
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Drop all the cached images.
+    pub fn clear(&mut self) {
+        self.images.clear();
+        self.recency.clear();
+    }
+
+    /// Drop the glyphs used least recently until at most `len` are left.
+    fn shrink_to(&mut self, len: usize) {
+        while self.images.len() > len {
+            match self.recency.pop_first() {
+                Some((_, cache_key)) => self.images.remove(&cache_key),
+                None => break,
+            };
+        }
+    }
+}
+
+fn render_glyph(
+    font_system: &mut FontSystem,
+    context: &mut ScaleContext,
+    cache_key: CacheKey,
+) -> Option<Image> {
+    let font = font_system.get_font(cache_key.font_id)?;
+    let index = font_system.db().face(cache_key.font_id)?.index;
+    let font_ref = FontRef::from_index(font.data(), index as usize)?;
+
+    let mut scaler = context
+        .builder(font_ref)
+        .size(f32::from_bits(cache_key.font_size_bits))
+        .hint(true)
+        .build();
+
+    let offset = Vector::new(cache_key.x_bin.as_float(), cache_key.y_bin.as_float());
+    let transform = if cache_key.flags.contains(CacheKeyFlags::FAKE_ITALIC) {
+        Some(Transform::skew(
+            Angle::from_degrees(14.0),
+            Angle::from_degrees(0.0),
+        ))
+    } else {
+        None
+    };
+
+    Render::new(&[
+        Source::ColorOutline(0),
+        Source::ColorBitmap(StrikeWith::BestFit),
+        Source::Outline,
+    ])
+    .format(Format::Alpha)
+    .offset(offset)
+    .transform(transform)
+    .render(&mut scaler, cache_key.glyph_id)
+}
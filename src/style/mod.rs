@@ -1,3 +1,7 @@
+mod font;
+mod glyph;
 mod text;
 
+pub use font::*;
+pub use glyph::*;
 pub use text::*;
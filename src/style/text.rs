@@ -1,24 +1,42 @@
-use alloc::string::String;
-use cosmic_text::{Buffer, FontSystem, Metrics};
+use alloc::{string::String, vec::Vec};
+use cosmic_text::{Attrs, Buffer, FontSystem, Metrics, Shaping};
+use swash::scale::image::Content;
 
 use crate::{color::Color, display::DisplayDriver};
 
-#[allow(unused)]
+use super::{FontFamily, FontRegistry, FontStyle, FontWeight};
+
 pub struct TextBuilder {
-    font_system: FontSystem,
+    fonts: FontRegistry,
+    family: FontFamily,
+    weight: FontWeight,
+    style: FontStyle,
     metrics: Metrics,
     text: String,
     position: (usize, usize),
+    size: Option<(usize, usize)>,
     color: Color,
 }
 
 impl Default for TextBuilder {
     fn default() -> Self {
+        Self::new(&FontRegistry::global())
+    }
+}
+
+impl TextBuilder {
+    /// Create a text builder which draws with the fonts in `fonts`. \
+    /// `TextBuilder::default` uses the global registry.
+    pub fn new(fonts: &FontRegistry) -> Self {
         Self {
-            font_system: FontSystem::new(),
+            fonts: fonts.clone(),
+            family: FontFamily::SansSerif,
+            weight: FontWeight::NORMAL,
+            style: FontStyle::Normal,
             metrics: Metrics::new(14.0, 10.0),
             text: String::new(),
             position: (0, 0),
+            size: None,
             color: Color::new_rgb(0xff, 0xff, 0xff),
         }
     }
@@ -40,6 +58,13 @@ impl TextBuilder {
         self
     }
 
+    /// Limit the text to an area of `size` starting at `position`. \
+    /// Lines are wrapped at its width, and anything outside of it is clipped.
+    pub fn size(&mut self, size: (usize, usize)) -> &mut Self {
+        self.size = Some(size);
+        self
+    }
+
     pub fn font_size(&mut self, font_size: f32) -> &mut Self {
         self.metrics.font_size = font_size;
         self
@@ -50,6 +75,26 @@ impl TextBuilder {
         self
     }
 
+    pub fn family(&mut self, family: FontFamily) -> &mut Self {
+        self.family = family;
+        self
+    }
+
+    pub fn weight(&mut self, weight: FontWeight) -> &mut Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn style(&mut self, style: FontStyle) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    pub fn color(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+        self
+    }
+
     pub fn text<S>(&mut self, text: S) -> &mut Self
     where
         String: From<S>,
@@ -60,7 +105,166 @@ impl TextBuilder {
 }
 
 impl TextBuilder {
-    pub fn draw<T: DisplayDriver>(&mut self, _driver: &mut T) {
-        let _font_buffer = Buffer::new(&mut self.font_system, self.metrics);
+    /// Shape the text and draw it into the driver, starting at `position`. \
+    /// Glyphs are blended into what the driver already contains, and anything outside of it is clipped. \
+    /// Nothing is drawn if no font is loaded.
+    /// ## Example
+    /// Every character of the Fur Blocks test font except the space is a box,
+    /// 4/5 of the advance wide and 7/10 of the font size high, sitting on the baseline.
+    /// ``` rust
+    /// use fur::color::Color;
+    /// use fur::display::{ColorBuffer, DisplayDriver};
+    /// use fur::style::{FontRegistry, TextBuilder};
+    ///
+    /// let fonts = FontRegistry::new();
+    /// fonts.load_font(include_bytes!("../../assets/fonts/FurBlocks.ttf"));
+    /// let mut buffer = ColorBuffer::new(40, 20);
+    /// let mut builder = TextBuilder::new(&fonts);
+    /// builder.text("Hi !").position((2, 2)).font_size(10.0).line_height(10.0);
+    /// builder.draw(&mut buffer);
+    /// assert_eq!(builder.measure(), (20, 10));
+    ///
+    /// let mut pixels = vec![Color::new_rgb(0, 0, 0); 40 * 20];
+    /// buffer.read(0, 0, 40, 20, &mut pixels);
+    /// let rgb = |x: usize, y: usize| pixels[y * 40 + x].as_rgb_tuple();
+    /// for y in 0..20 {
+    ///     for x in 0..40 {
+    ///         // The boxes of "H", "i" and "!". Their left and right columns are partly covered.
+    ///         let boxes = [3..6, 8..11, 18..21];
+    ///         let covered = (3..10).contains(&y) && boxes.iter().any(|xs| xs.contains(&x));
+    ///         let measured = (2..22).contains(&x) && (2..12).contains(&y);
+    ///         if covered {
+    ///             assert_eq!(rgb(x, y), (0xff, 0xff, 0xff), "({}, {})", x, y);
+    ///         } else if !measured || (12..17).contains(&x) {
+    ///             // Outside of the measured area, and the space.
+    ///             assert_eq!(rgb(x, y), (0, 0, 0), "({}, {})", x, y);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn draw<T: DisplayDriver + ?Sized>(&mut self, driver: &mut T) {
+        let (x, y) = self.position;
+        let (width, height) = match (driver.size(), self.size) {
+            ((width, height), Some(size)) => (
+                width.min(x.saturating_add(size.0)),
+                height.min(y.saturating_add(size.1)),
+            ),
+            (size, None) => size,
+        };
+        if x >= width || y >= height {
+            return;
+        }
+
+        // The glyphs are rasterized first, so that the fonts are not locked while the driver runs.
+        let mut pixels = Vec::new();
+        let mut guard = self.fonts.lock();
+        let fonts = &mut *guard;
+        // cosmic-text panics when there is no font to fall back to.
+        if fonts.font_system.db().faces().next().is_none() {
+            return;
+        }
+
+        let buffer = self.shape(
+            &mut fonts.font_system,
+            Some((width - x) as f32),
+            Some((height - y) as f32),
+        );
+        for run in buffer.layout_runs() {
+            for glyph in run.glyphs {
+                let physical = glyph.physical((x as f32, y as f32), 1.0);
+                let image = match fonts
+                    .glyph_cache
+                    .image(&mut fonts.font_system, physical.cache_key)
+                {
+                    Some(image) => image,
+                    None => continue,
+                };
+
+                let left = physical.x + image.placement.left;
+                let top = run.line_y as i32 + physical.y - image.placement.top;
+                let image_width = image.placement.width as usize;
+
+                for dy in 0..image.placement.height as usize {
+                    for dx in 0..image_width {
+                        let t_x = left + dx as i32;
+                        let t_y = top + dy as i32;
+                        if t_x < 0 || t_y < 0 || t_x as usize >= width || t_y as usize >= height {
+                            continue;
+                        }
+
+                        let color = match image.content {
+                            Content::Mask => {
+                                let coverage = image.data[dy * image_width + dx];
+                                let (alpha, red, green, blue) = self.color.as_argb_tuple();
+                                let alpha = alpha as u32 * coverage as u32 / 0xff;
+                                Color::new_argb(alpha as u8, red, green, blue)
+                            }
+                            Content::Color => {
+                                let offset = (dy * image_width + dx) * 4;
+                                let [red, green, blue, alpha] =
+                                    [0, 1, 2, 3].map(|i| image.data[offset + i]);
+                                Color::new_argb(alpha, red, green, blue)
+                            }
+                            Content::SubpixelMask => continue,
+                        };
+                        if color.alpha() != 0 {
+                            pixels.push((t_x as usize, t_y as usize, color));
+                        }
+                    }
+                }
+            }
+        }
+        drop(guard);
+
+        for (x, y, color) in pixels {
+            let mut base_color = [Color::new_rgb(0, 0, 0)];
+            driver.read(x, y, 1, 1, &mut base_color);
+            let color = base_color[0].mix(&color);
+            driver.write(x, y, 1, 1, &color);
+        }
+    }
+
+    /// The size the text takes when it is drawn, wrapped at the width set by `size`. \
+    /// It is `(0, 0)` if no font is loaded.
+    pub fn measure(&self) -> (usize, usize) {
+        let mut fonts = self.fonts.lock();
+        if fonts.font_system.db().faces().next().is_none() {
+            return (0, 0);
+        }
+
+        let buffer = self.shape(
+            &mut fonts.font_system,
+            self.size.map(|size| size.0 as f32),
+            None,
+        );
+        // `f32::ceil` needs std.
+        let ceil = |value: f32| {
+            let whole = value as usize;
+            whole + (value > whole as f32) as usize
+        };
+        buffer.layout_runs().fold((0, 0), |(width, height), run| {
+            (
+                width.max(ceil(run.line_w)),
+                height.max(ceil(run.line_top + run.line_height)),
+            )
+        })
+    }
+
+    fn shape(
+        &self,
+        font_system: &mut FontSystem,
+        width: Option<f32>,
+        height: Option<f32>,
+    ) -> Buffer {
+        let attrs = Attrs::new()
+            .family(self.family.as_family())
+            .weight(self.weight)
+            .style(self.style);
+
+        let mut buffer = Buffer::new(font_system, self.metrics);
+        buffer.set_size(font_system, width, height);
+        buffer.set_text(font_system, &self.text, &attrs, Shaping::Advanced);
+        buffer.shape_until_scroll(font_system, false);
+        buffer
     }
 }
@@ -0,0 +1,177 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::{
+    color::Color,
+    display::{DisplayDriver, Rect},
+    event::{Event, KeyCode, KeyState, PointerAction, PointerButton},
+    style::FontRegistry,
+};
+
+use super::{Label, Response, Widget};
+
+/// A button with a text, which calls a function when it is clicked. \
+/// It is clicked when the left button is released on it after being pressed on it,
+/// or when Enter or Space is pressed while it has the focus.
+pub struct Button {
+    label: Label,
+    padding: usize,
+    colors: ButtonColors,
+    /// The size from the last layout, to know whether the pointer is released on the button.
+    size: (usize, usize),
+    hovered: bool,
+    pressed: bool,
+    on_click: Option<Box<dyn FnMut()>>,
+}
+
+/// The colors used to draw a button.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ButtonColors {
+    pub border: Color,
+    pub background: Color,
+    /// The background while the pointer is over the button.
+    pub hovered: Color,
+    /// The background while the button is held down.
+    pub pressed: Color,
+}
+
+impl Default for ButtonColors {
+    fn default() -> Self {
+        Self {
+            border: Color::new_rgb(0x5a, 0x5a, 0x5a),
+            background: Color::new_rgb(0x3c, 0x3c, 0x3c),
+            hovered: Color::new_rgb(0x4a, 0x4a, 0x4a),
+            pressed: Color::new_rgb(0x1a, 0x1a, 0x1a),
+        }
+    }
+}
+
+impl Button {
+    pub fn new<S>(text: S) -> Self
+    where
+        String: From<S>,
+    {
+        Self {
+            label: Label::new(text),
+            padding: 6,
+            colors: ButtonColors::default(),
+            size: (0, 0),
+            hovered: false,
+            pressed: false,
+            on_click: None,
+        }
+    }
+}
+
+impl Button {
+    pub fn text<S>(&mut self, text: S) -> &mut Self
+    where
+        String: From<S>,
+    {
+        self.label.text(text);
+        self
+    }
+
+    /// Set the space between the border and the text.
+    pub fn padding(&mut self, padding: usize) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn colors(&mut self, colors: ButtonColors) -> &mut Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Set the fonts used for the text. The global registry is used by default.
+    pub fn fonts(&mut self, fonts: &FontRegistry) -> &mut Self {
+        self.label.fonts(fonts);
+        self
+    }
+
+    /// Set the function called when the button is clicked.
+    pub fn on_click<F: FnMut() + 'static>(&mut self, on_click: F) -> &mut Self {
+        self.on_click = Some(Box::new(on_click));
+        self
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    fn click(&mut self) {
+        if let Some(on_click) = &mut self.on_click {
+            on_click();
+        }
+    }
+}
+
+impl Widget for Button {
+    fn measure(&self, _children: &[(usize, usize)]) -> (usize, usize) {
+        let (width, height) = self.label.measure(&[]);
+        let padding = (self.padding + 1) * 2;
+        (width + padding, height + padding)
+    }
+
+    fn layout(&mut self, size: (usize, usize), _children: &[(usize, usize)]) -> Vec<Rect> {
+        self.size = size;
+        Vec::new()
+    }
+
+    fn paint(&mut self, driver: &mut dyn DisplayDriver) {
+        let (width, height) = driver.size();
+        let background = match (self.pressed, self.hovered) {
+            (true, _) => &self.colors.pressed,
+            (false, true) => &self.colors.hovered,
+            (false, false) => &self.colors.background,
+        };
+        driver.write(0, 0, width, height, &self.colors.border);
+        driver.write(
+            1,
+            1,
+            width.saturating_sub(2),
+            height.saturating_sub(2),
+            background,
+        );
+
+        let (text_width, text_height) = self.label.measure(&[]);
+        let x = width.saturating_sub(text_width) / 2;
+        let y = height.saturating_sub(text_height) / 2;
+        self.label
+            .builder()
+            .position((x, y))
+            .size((width - x, height - y))
+            .draw(driver);
+    }
+
+    fn handle_event(&mut self, event: &Event) -> Response {
+        match event {
+            Event::PointerEnter | Event::PointerLeave => {
+                self.hovered = *event == Event::PointerEnter;
+                Response::Repaint
+            }
+            Event::Pointer(pointer_event) => match pointer_event.action {
+                PointerAction::ButtonDown(PointerButton::Left) => {
+                    self.pressed = true;
+                    Response::Repaint
+                }
+                PointerAction::ButtonUp(PointerButton::Left) if self.pressed => {
+                    self.pressed = false;
+                    let (width, height) = self.size;
+                    if Rect::new(0, 0, width, height).contains(pointer_event.x, pointer_event.y) {
+                        self.click();
+                    }
+                    Response::Repaint
+                }
+                _ => Response::Ignored,
+            },
+            Event::Key(key_event) => match (key_event.keycode, key_event.state) {
+                (KeyCode::Enter | KeyCode::Space, KeyState::Pressed) => {
+                    self.click();
+                    Response::Handled
+                }
+                _ => Response::Ignored,
+            },
+            _ => Response::Ignored,
+        }
+    }
+}
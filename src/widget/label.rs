@@ -0,0 +1,79 @@
+use alloc::string::String;
+
+use crate::{
+    color::Color,
+    display::DisplayDriver,
+    style::{FontRegistry, TextBuilder},
+};
+
+use super::Widget;
+
+/// A line or a paragraph of text. It is as large as its text, and the text is wrapped if the label is narrower.
+pub struct Label {
+    text: String,
+    color: Color,
+    font_size: f32,
+    fonts: FontRegistry,
+}
+
+impl Label {
+    pub fn new<S>(text: S) -> Self
+    where
+        String: From<S>,
+    {
+        Self {
+            text: String::from(text),
+            color: Color::new_rgb(0xe0, 0xe0, 0xe0),
+            font_size: 14.0,
+            fonts: FontRegistry::global(),
+        }
+    }
+}
+
+impl Label {
+    pub fn text<S>(&mut self, text: S) -> &mut Self
+    where
+        String: From<S>,
+    {
+        self.text = String::from(text);
+        self
+    }
+
+    pub fn color(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    pub fn font_size(&mut self, font_size: f32) -> &mut Self {
+        self.font_size = font_size;
+        self
+    }
+
+    /// Set the fonts used for the text. The global registry is used by default.
+    pub fn fonts(&mut self, fonts: &FontRegistry) -> &mut Self {
+        self.fonts = fonts.clone();
+        self
+    }
+
+    /// The text builder which draws the label.
+    pub(crate) fn builder(&self) -> TextBuilder {
+        let mut builder = TextBuilder::new(&self.fonts);
+        builder
+            .font_size(self.font_size)
+            .line_height(self.font_size * 1.4)
+            .color(self.color.clone())
+            .text(self.text.as_str());
+        builder
+    }
+}
+
+impl Widget for Label {
+    fn measure(&self, _children: &[(usize, usize)]) -> (usize, usize) {
+        self.builder().measure()
+    }
+
+    fn paint(&mut self, driver: &mut dyn DisplayDriver) {
+        let size = driver.size();
+        self.builder().size(size).draw(driver);
+    }
+}
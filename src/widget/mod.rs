@@ -0,0 +1,76 @@
+//! Widgets are the parts of a user interface, such as labels, buttons and panels. \
+//! They live in a `WidgetTree`, which lays them out, paints the parts which have changed
+//! and sends events to them.
+
+mod button;
+mod label;
+mod panel;
+mod tree;
+
+pub use button::*;
+pub use label::*;
+pub use panel::*;
+pub use tree::*;
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    display::{DisplayDriver, Rect},
+    event::Event,
+};
+
+/// What a widget did with an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    /// The widget doesn't use the event, so it is sent to the parent.
+    Ignored,
+    /// The widget used the event and looks the same.
+    Handled,
+    /// The widget used the event and has to be painted again.
+    Repaint,
+    /// The widget used the event and its size may change, so the tree has to be laid out again.
+    Relayout,
+}
+
+/// A part of a user interface. \
+/// Widgets don't own their children, the `WidgetTree` does. It asks every widget for its size from the leaves up,
+/// then gives every widget its rectangle from the root down.
+pub trait Widget: private::AsAny + 'static {
+    /// The size the widget wants, knowing the sizes its children want.
+    fn measure(&self, children: &[(usize, usize)]) -> (usize, usize);
+
+    /// Place the children in the widget, which has got `size`. The rectangles are relative to the widget. \
+    /// By default every child covers the whole widget.
+    fn layout(&mut self, size: (usize, usize), children: &[(usize, usize)]) -> Vec<Rect> {
+        vec![Rect::new(0, 0, size.0, size.1); children.len()]
+    }
+
+    /// Draw the widget. The driver covers the widget only, and its children are painted on the top later.
+    fn paint(&mut self, driver: &mut dyn DisplayDriver);
+
+    /// Handle an event. The position of a pointer event is relative to the widget.
+    fn handle_event(&mut self, event: &Event) -> Response {
+        let _ = event;
+        Response::Ignored
+    }
+}
+
+mod private {
+    use core::any::Any;
+
+    /// Lets `WidgetTree` get the concrete type of a widget back. It is implemented for every type.
+    pub trait AsAny {
+        fn as_any(&self) -> &dyn Any;
+        fn as_any_mut(&mut self) -> &mut dyn Any;
+    }
+
+    impl<T: Any> AsAny for T {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+}
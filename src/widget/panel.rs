@@ -0,0 +1,121 @@
+use alloc::vec::Vec;
+
+use crate::{
+    color::Color,
+    display::{DisplayDriver, Rect},
+};
+
+use super::Widget;
+
+/// The direction children are placed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Axis {
+    /// From the left to the right.
+    Horizontal,
+    /// From the top to the bottom.
+    #[default]
+    Vertical,
+}
+
+/// A widget which places its children one after another along an axis. \
+/// Every child gets the size it wants along the axis, and the whole panel across it.
+pub struct Panel {
+    axis: Axis,
+    spacing: usize,
+    padding: usize,
+    background: Option<Color>,
+}
+
+impl Panel {
+    /// Create a panel with no spacing, no padding and no background.
+    pub fn new(axis: Axis) -> Self {
+        Self {
+            axis,
+            spacing: 0,
+            padding: 0,
+            background: None,
+        }
+    }
+}
+
+impl Panel {
+    pub fn axis(&mut self, axis: Axis) -> &mut Self {
+        self.axis = axis;
+        self
+    }
+
+    /// Set the space between two children.
+    pub fn spacing(&mut self, spacing: usize) -> &mut Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Set the space between the edges of the panel and the children.
+    pub fn padding(&mut self, padding: usize) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Fill the panel with a color. Without a background, the panel shows what is below it.
+    pub fn background(&mut self, background: Color) -> &mut Self {
+        self.background = Some(background);
+        self
+    }
+}
+
+impl Widget for Panel {
+    fn measure(&self, children: &[(usize, usize)]) -> (usize, usize) {
+        let spacing = self.spacing * children.len().saturating_sub(1);
+        let (along, across) = children
+            .iter()
+            .map(|&(width, height)| match self.axis {
+                Axis::Horizontal => (width, height),
+                Axis::Vertical => (height, width),
+            })
+            .fold((spacing, 0), |(along, across), (length, thickness)| {
+                (along + length, across.max(thickness))
+            });
+        let (along, across) = (along + self.padding * 2, across + self.padding * 2);
+        match self.axis {
+            Axis::Horizontal => (along, across),
+            Axis::Vertical => (across, along),
+        }
+    }
+
+    fn layout(&mut self, size: (usize, usize), children: &[(usize, usize)]) -> Vec<Rect> {
+        let padding = self.padding as isize;
+        let mut offset = padding;
+        children
+            .iter()
+            .map(|&(width, height)| {
+                let rect = match self.axis {
+                    Axis::Horizontal => Rect::new(
+                        offset,
+                        padding,
+                        width,
+                        size.1.saturating_sub(self.padding * 2),
+                    ),
+                    Axis::Vertical => Rect::new(
+                        padding,
+                        offset,
+                        size.0.saturating_sub(self.padding * 2),
+                        height,
+                    ),
+                };
+                let length = match self.axis {
+                    Axis::Horizontal => width,
+                    Axis::Vertical => height,
+                };
+                offset += (length + self.spacing) as isize;
+                rect
+            })
+            .collect()
+    }
+
+    fn paint(&mut self, driver: &mut dyn DisplayDriver) {
+        if let Some(background) = &self.background {
+            let (width, height) = driver.size();
+            driver.write(0, 0, width, height, background);
+        }
+    }
+}
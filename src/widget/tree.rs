@@ -0,0 +1,572 @@
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+use crate::{
+    color::Color,
+    display::{Damage, DisplayDriver, Rect},
+    event::{Event, PointerAction},
+};
+
+use super::{Response, Widget};
+
+/// The id of a widget in a `WidgetTree`. It is never used again after the widget is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WidgetId {
+    id: usize,
+}
+
+struct Node {
+    widget: Box<dyn Widget>,
+    parent: Option<WidgetId>,
+    children: Vec<WidgetId>,
+    /// The area of the widget in the driver the tree is painted into.
+    rect: Rect,
+    /// The size the widget wanted on the last layout.
+    wanted: (usize, usize),
+}
+
+/// The widgets of a user interface, each of them with its parent and its children. \
+/// The root covers the whole tree, and the other widgets get their rectangles from their parents. \
+/// Changing a widget invalidates its rectangle, and `paint` only paints the widgets in the invalidated areas,
+/// so painting into a layer only damages these areas of the display.
+/// ## Example
+/// ``` rust
+/// use std::sync::{
+///     atomic::{AtomicBool, Ordering},
+///     Arc,
+/// };
+///
+/// use fur::color::Color;
+/// use fur::display::{DrawBuffer, Rect};
+/// use fur::event::{Event, PointerAction, PointerButton, PointerEvent};
+/// use fur::pixel::PixelFormat;
+/// use fur::widget::{Axis, Button, Label, Panel, WidgetTree};
+///
+/// let mut buffer = DrawBuffer::new(200, 100, PixelFormat::Argb);
+/// let mut tree = WidgetTree::new(Rect::new(0, 0, 200, 100));
+///
+/// let mut panel = Panel::new(Axis::Vertical);
+/// panel.padding(10).spacing(5).background(Color::new_rgb(0x20, 0x20, 0x20));
+/// let root = tree.set_root(panel);
+/// tree.add_child(root, Label::new("Save the file?"));
+///
+/// let clicked = Arc::new(AtomicBool::new(false));
+/// let mut button = Button::new("Save");
+/// let flag = clicked.clone();
+/// button.on_click(move || flag.store(true, Ordering::Relaxed));
+/// let button = tree.add_child(root, button).unwrap();
+/// tree.paint(&mut buffer);
+///
+/// let rect = tree.rect(button).unwrap();
+/// let (x, y) = (rect.x + 1, rect.y + 1);
+/// for action in [
+///     PointerAction::ButtonDown(PointerButton::Left),
+///     PointerAction::ButtonUp(PointerButton::Left),
+/// ] {
+///     assert_eq!(tree.handle_event(&Event::Pointer(PointerEvent::new(x, y, action))), Some(button));
+/// }
+/// assert!(clicked.load(Ordering::Relaxed));
+/// assert!(tree.needs_paint());
+/// ```
+pub struct WidgetTree {
+    nodes: BTreeMap<WidgetId, Node>,
+    root: Option<WidgetId>,
+    next_widget_id: usize,
+    bounds: Rect,
+    /// The color the invalidated areas are filled with before the widgets are painted.
+    background: Option<Color>,
+    needs_layout: bool,
+    damage: Damage,
+    /// The widget which gets the keyboard events.
+    focused: Option<WidgetId>,
+    /// The widget under the pointer.
+    hovered: Option<WidgetId>,
+    /// The widget which gets all the pointer events while buttons are held down.
+    grab: Option<WidgetId>,
+    pressed_buttons: usize,
+}
+
+impl WidgetTree {
+    /// Create an empty tree which covers `bounds` of the driver it is painted into.
+    pub fn new(bounds: Rect) -> Self {
+        let mut damage = Damage::new();
+        damage.add(bounds);
+        Self {
+            nodes: BTreeMap::new(),
+            root: None,
+            next_widget_id: 0,
+            bounds,
+            background: None,
+            needs_layout: true,
+            damage,
+            focused: None,
+            hovered: None,
+            grab: None,
+            pressed_buttons: 0,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Move or resize the tree. Everything is laid out and painted again.
+    pub fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+        self.needs_layout = true;
+        self.damage.add(bounds);
+    }
+
+    /// Fill the invalidated areas with a color before painting the widgets,
+    /// so that widgets which don't cover their whole area are not painted over their old look. \
+    /// Without a background, the widgets are painted over what the driver contains.
+    pub fn set_background(&mut self, background: Option<Color>) {
+        self.background = background;
+        self.damage.add(self.bounds);
+    }
+}
+
+impl WidgetTree {
+    /// Replace all the widgets with `widget`, which covers the whole tree.
+    pub fn set_root<W: Widget>(&mut self, widget: W) -> WidgetId {
+        self.nodes.clear();
+        self.focused = None;
+        self.hovered = None;
+        self.grab = None;
+        self.damage.add(self.bounds);
+        let id = self.insert(None, Box::new(widget));
+        self.root = Some(id);
+        id
+    }
+
+    pub fn root(&self) -> Option<WidgetId> {
+        self.root
+    }
+
+    /// Add a widget after the other children of `parent`.
+    pub fn add_child<W: Widget>(&mut self, parent: WidgetId, widget: W) -> Option<WidgetId> {
+        if !self.nodes.contains_key(&parent) {
+            return None;
+        }
+        let id = self.insert(Some(parent), Box::new(widget));
+        self.nodes.get_mut(&parent)?.children.push(id);
+        Some(id)
+    }
+
+    /// Remove a widget with all its children.
+    pub fn remove(&mut self, id: WidgetId) -> Option<()> {
+        let node = self.nodes.get(&id)?;
+        self.damage.add(node.rect);
+        match node.parent {
+            Some(parent) => self
+                .nodes
+                .get_mut(&parent)?
+                .children
+                .retain(|child| *child != id),
+            None => self.root = None,
+        }
+
+        let mut removed = Vec::from([id]);
+        while let Some(id) = removed.pop() {
+            if let Some(node) = self.nodes.remove(&id) {
+                removed.extend(node.children);
+            }
+        }
+        for widget in [&mut self.focused, &mut self.hovered, &mut self.grab] {
+            if widget.map_or(false, |id| !self.nodes.contains_key(&id)) {
+                *widget = None;
+            }
+        }
+        self.needs_layout = true;
+        Some(())
+    }
+
+    pub fn parent(&self, id: WidgetId) -> Option<WidgetId> {
+        self.nodes.get(&id)?.parent
+    }
+
+    pub fn children(&self, id: WidgetId) -> Option<&[WidgetId]> {
+        Some(&self.nodes.get(&id)?.children)
+    }
+
+    /// The area of a widget in the driver, from the last layout.
+    pub fn rect(&self, id: WidgetId) -> Option<Rect> {
+        Some(self.nodes.get(&id)?.rect)
+    }
+
+    /// Get a widget, if it has the type `W`.
+    pub fn get<W: Widget>(&self, id: WidgetId) -> Option<&W> {
+        (*self.nodes.get(&id)?.widget).as_any().downcast_ref()
+    }
+
+    /// Get a widget to change it, if it has the type `W`. \
+    /// The tree is laid out again and the widget is painted again, as its size or its look may change.
+    pub fn get_mut<W: Widget>(&mut self, id: WidgetId) -> Option<&mut W> {
+        let node = self.nodes.get_mut(&id)?;
+        let widget = (*node.widget).as_any_mut().downcast_mut()?;
+        self.damage.add(node.rect);
+        self.needs_layout = true;
+        Some(widget)
+    }
+
+    fn insert(&mut self, parent: Option<WidgetId>, widget: Box<dyn Widget>) -> WidgetId {
+        let id = WidgetId {
+            id: self.next_widget_id,
+        };
+        self.next_widget_id += 1;
+        self.nodes.insert(
+            id,
+            Node {
+                widget,
+                parent,
+                children: Vec::new(),
+                rect: Rect::default(),
+                wanted: (0, 0),
+            },
+        );
+        self.needs_layout = true;
+        id
+    }
+}
+
+/// Widgets are measured from the leaves up and placed from the root down. \
+/// A widget whose rectangle changes is painted again, in its old and its new areas.
+impl WidgetTree {
+    /// Paint a widget again on the next `paint`.
+    pub fn invalidate(&mut self, id: WidgetId) -> Option<()> {
+        let rect = self.nodes.get(&id)?.rect;
+        self.damage.add(rect);
+        Some(())
+    }
+
+    /// Lay the tree out again on the next `paint`, because the size of a widget may have changed.
+    pub fn invalidate_layout(&mut self) {
+        self.needs_layout = true;
+    }
+
+    /// Whether `paint` has anything to do.
+    pub fn needs_paint(&self) -> bool {
+        self.needs_layout || !self.damage.is_empty()
+    }
+
+    /// Lay the tree out if it has changed. `paint` and `handle_event` do it before anything else.
+    pub fn layout(&mut self) {
+        if !self.needs_layout {
+            return;
+        }
+        self.needs_layout = false;
+        if let Some(root) = self.root {
+            self.measure(root);
+            self.place(root, self.bounds);
+        }
+    }
+
+    /// Find the deepest widget at a point of the driver.
+    pub fn widget_at(&self, x: isize, y: isize) -> Option<WidgetId> {
+        let mut id = self
+            .root
+            .filter(|root| self.nodes[root].rect.contains(x, y))?;
+        while let Some(child) = self.nodes[&id]
+            .children
+            .iter()
+            .rev()
+            .find(|child| self.nodes[child].rect.contains(x, y))
+        {
+            id = *child;
+        }
+        Some(id)
+    }
+
+    /// Lay the tree out and paint the invalidated areas into the driver, parents before their children.
+    pub fn paint(&mut self, driver: &mut dyn DisplayDriver) {
+        self.layout();
+        let (width, height) = driver.size();
+        let visible = Rect::new(0, 0, width, height);
+        for rect in self.damage.take() {
+            let clip = match rect
+                .intersection(&self.bounds)
+                .and_then(|rect| rect.intersection(&visible))
+            {
+                Some(clip) => clip,
+                None => continue,
+            };
+            if let Some(background) = &self.background {
+                driver.write(
+                    clip.x as usize,
+                    clip.y as usize,
+                    clip.width,
+                    clip.height,
+                    background,
+                );
+            }
+            if let Some(root) = self.root {
+                self.paint_node(root, driver, &clip);
+            }
+        }
+    }
+
+    fn measure(&mut self, id: WidgetId) -> (usize, usize) {
+        let children = self.nodes[&id].children.clone();
+        let sizes = children
+            .into_iter()
+            .map(|child| self.measure(child))
+            .collect::<Vec<_>>();
+        let node = self.nodes.get_mut(&id).unwrap();
+        node.wanted = node.widget.measure(&sizes);
+        node.wanted
+    }
+
+    fn place(&mut self, id: WidgetId, rect: Rect) {
+        let node = self.nodes.get_mut(&id).unwrap();
+        if node.rect != rect {
+            self.damage.add(node.rect);
+            self.damage.add(rect);
+            node.rect = rect;
+        }
+
+        let children = node.children.clone();
+        let sizes = children
+            .iter()
+            .map(|child| self.nodes[child].wanted)
+            .collect::<Vec<_>>();
+        let node = self.nodes.get_mut(&id).unwrap();
+        let mut rects = node
+            .widget
+            .layout((rect.width, rect.height), &sizes)
+            .into_iter();
+        for child in children {
+            // Children are kept inside their parent.
+            let child_rect = rects
+                .next()
+                .map(|child_rect| {
+                    Rect::new(
+                        rect.x + child_rect.x,
+                        rect.y + child_rect.y,
+                        child_rect.width,
+                        child_rect.height,
+                    )
+                })
+                .and_then(|child_rect| child_rect.intersection(&rect))
+                .unwrap_or(Rect::new(rect.x, rect.y, 0, 0));
+            self.place(child, child_rect);
+        }
+    }
+
+    fn paint_node(&mut self, id: WidgetId, driver: &mut dyn DisplayDriver, clip: &Rect) {
+        let node = self.nodes.get_mut(&id).unwrap();
+        let clip = match node.rect.intersection(clip) {
+            Some(clip) => clip,
+            None => return,
+        };
+        node.widget.paint(&mut Clip {
+            driver,
+            rect: node.rect,
+            clip,
+        });
+        for child in node.children.clone() {
+            self.paint_node(child, driver, &clip);
+        }
+    }
+}
+
+/// Pointer events go to the widget under the pointer, or to the widget which got the button down
+/// until all the buttons are released. Keyboard events go to the focused widget, or to the root. \
+/// A widget which ignores an event passes it on to its parent.
+impl WidgetTree {
+    pub fn focused(&self) -> Option<WidgetId> {
+        self.focused
+    }
+
+    /// Give the keyboard focus to a widget, sending `FocusOut` and `FocusIn` to the widgets.
+    pub fn focus(&mut self, id: WidgetId) -> Option<()> {
+        if !self.nodes.contains_key(&id) {
+            return None;
+        }
+        if self.focused != Some(id) {
+            if let Some(old) = self.focused {
+                self.send(old, &Event::FocusOut);
+            }
+            self.send(id, &Event::FocusIn);
+            self.focused = Some(id);
+        }
+        Some(())
+    }
+
+    /// Send an event to the widgets, and return the widget which handled it. \
+    /// Pressing a button on a widget which handles it focuses the widget. \
+    /// `FocusIn` and `FocusOut` of the whole tree go to the focused widget only. \
+    /// `PointerLeave` only updates the widget under the pointer, and other events are not handled.
+    pub fn handle_event(&mut self, event: &Event) -> Option<WidgetId> {
+        self.layout();
+        match event {
+            Event::Pointer(pointer_event) => {
+                let hovered = self.widget_at(pointer_event.x, pointer_event.y);
+                self.set_hovered(hovered);
+                let target = self.grab.or(hovered);
+                match pointer_event.action {
+                    PointerAction::ButtonDown(_) => {
+                        self.pressed_buttons += 1;
+                        if self.grab.is_none() {
+                            self.grab = target;
+                        }
+                        let handler = self.bubble(target?, event)?;
+                        self.focus(handler);
+                        Some(handler)
+                    }
+                    PointerAction::ButtonUp(_) => {
+                        self.pressed_buttons = self.pressed_buttons.saturating_sub(1);
+                        if self.pressed_buttons == 0 {
+                            self.grab = None;
+                        }
+                        self.bubble(target?, event)
+                    }
+                    _ => self.bubble(target?, event),
+                }
+            }
+            Event::Key(_) => self.bubble(self.focused.or(self.root)?, event),
+            Event::FocusIn | Event::FocusOut => {
+                let focused = self.focused?;
+                (self.send(focused, event) != Response::Ignored).then_some(focused)
+            }
+            Event::PointerLeave => {
+                self.set_hovered(None);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Send an event to a widget and then to its parents until one of them handles it.
+    fn bubble(&mut self, mut id: WidgetId, event: &Event) -> Option<WidgetId> {
+        loop {
+            if self.send(id, event) != Response::Ignored {
+                return Some(id);
+            }
+            id = self.nodes.get(&id)?.parent?;
+        }
+    }
+
+    /// Send an event to a widget only, and do what its response asks for.
+    fn send(&mut self, id: WidgetId, event: &Event) -> Response {
+        let node = match self.nodes.get_mut(&id) {
+            Some(node) => node,
+            None => return Response::Ignored,
+        };
+        let rect = node.rect;
+        let response = match event {
+            Event::Pointer(pointer_event) => node.widget.handle_event(&Event::Pointer(
+                pointer_event.moved_to(pointer_event.x - rect.x, pointer_event.y - rect.y),
+            )),
+            event => node.widget.handle_event(event),
+        };
+        match response {
+            Response::Repaint => self.damage.add(rect),
+            Response::Relayout => {
+                self.damage.add(rect);
+                self.needs_layout = true;
+            }
+            Response::Ignored | Response::Handled => {}
+        }
+        response
+    }
+
+    /// Move the pointer between widgets, sending `PointerLeave` and `PointerEnter` to them.
+    fn set_hovered(&mut self, hovered: Option<WidgetId>) {
+        if self.hovered == hovered {
+            return;
+        }
+        if let Some(old) = self.hovered {
+            self.send(old, &Event::PointerLeave);
+        }
+        if let Some(new) = hovered {
+            self.send(new, &Event::PointerEnter);
+        }
+        self.hovered = hovered;
+    }
+}
+
+/// The part of a driver a widget is painted into. \
+/// The widget sees its own rectangle with (0,0) at its top left corner, and anything outside the clip is dropped.
+struct Clip<'a> {
+    driver: &'a mut dyn DisplayDriver,
+    /// The area of the widget in the driver.
+    rect: Rect,
+    /// The area which can be read and written, in the driver.
+    clip: Rect,
+}
+
+impl Clip<'_> {
+    /// Clip a rectangle of the widget. Return the part left in the driver,
+    /// and its offset from the top left corner of the rectangle.
+    fn visible(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<(Rect, usize, usize)> {
+        let rect = Rect::new(
+            self.rect.x + x as isize,
+            self.rect.y + y as isize,
+            width,
+            height,
+        );
+        let clipped = rect.intersection(&self.clip)?;
+        Some((
+            clipped,
+            (clipped.x - rect.x) as usize,
+            (clipped.y - rect.y) as usize,
+        ))
+    }
+}
+
+impl DisplayDriver for Clip<'_> {
+    fn read(&self, x: usize, y: usize, width: usize, height: usize, pixels: &mut [Color]) {
+        let Some((area, left, top)) = self.visible(x, y, width, height) else {
+            return;
+        };
+        for dy in 0..area.height {
+            let start = (top + dy) * width + left;
+            if let Some(row) = pixels.get_mut(start..start + area.width) {
+                self.driver
+                    .read_span(area.x as usize, area.y as usize + dy, row);
+            }
+        }
+    }
+
+    fn write(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
+        if let Some((area, _, _)) = self.visible(x, y, width, height) {
+            self.driver.write(
+                area.x as usize,
+                area.y as usize,
+                area.width,
+                area.height,
+                color,
+            );
+        }
+    }
+
+    fn write_pixels(&mut self, x: usize, y: usize, width: usize, height: usize, pixels: &[Color]) {
+        let Some((area, left, top)) = self.visible(x, y, width, height) else {
+            return;
+        };
+        for dy in 0..area.height {
+            let start = (top + dy) * width + left;
+            if let Some(row) = pixels.get(start..start + area.width) {
+                self.driver
+                    .write_span(area.x as usize, area.y as usize + dy, row);
+            }
+        }
+    }
+
+    fn read_span(&self, x: usize, y: usize, pixels: &mut [Color]) {
+        self.read(x, y, pixels.len(), 1, pixels);
+    }
+
+    fn write_span(&mut self, x: usize, y: usize, pixels: &[Color]) {
+        self.write_pixels(x, y, pixels.len(), 1, pixels);
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.rect.width, self.rect.height)
+    }
+}
@@ -0,0 +1,422 @@
+use alloc::string::String;
+
+use crate::{
+    color::Color,
+    display::{DisplayDriver, Rect},
+    style::{FontRegistry, TextBuilder},
+};
+
+/// The colors used to draw a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowTheme {
+    pub border: Color,
+    pub title_bar: Color,
+    pub title_text: Color,
+    pub background: Color,
+    pub button: Color,
+    /// The background of the close button, which makes it stand out from the others.
+    pub close_button: Color,
+}
+
+impl Default for WindowTheme {
+    fn default() -> Self {
+        Self {
+            border: Color::new_rgb(0x3c, 0x3c, 0x3c),
+            title_bar: Color::new_rgb(0x1a, 0x1a, 0x1a),
+            title_text: Color::new_rgb(0xe0, 0xe0, 0xe0),
+            background: Color::new_rgb(0x2b, 0x2b, 0x2b),
+            button: Color::new_rgb(0xc0, 0xc0, 0xc0),
+            close_button: Color::new_rgb(0xc4, 0x2b, 0x1c),
+        }
+    }
+}
+
+/// The buttons on the right of the title bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowButton {
+    Close,
+    Maximize,
+    Minimize,
+}
+
+/// The edges and the corners a window can be resized from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl ResizeEdge {
+    /// Whether the edge moves the `(top, bottom, left, right)` sides of the window.
+    pub fn sides(&self) -> (bool, bool, bool, bool) {
+        match self {
+            Self::Top => (true, false, false, false),
+            Self::Bottom => (false, true, false, false),
+            Self::Left => (false, false, true, false),
+            Self::Right => (false, false, false, true),
+            Self::TopLeft => (true, false, true, false),
+            Self::TopRight => (true, false, false, true),
+            Self::BottomLeft => (false, true, true, false),
+            Self::BottomRight => (false, true, false, true),
+        }
+    }
+}
+
+/// The parts of a window, found by `WindowBuilder::area_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowArea {
+    Client,
+    /// The title bar except the buttons, where the window can be dragged.
+    TitleBar,
+    Button(WindowButton),
+    Edge(ResizeEdge),
+}
+
+/// Draws a window: a border, a title bar with the title and the buttons, and the client area. \
+/// The window is drawn at the origin of the driver, which is usually the layer of the window.
+/// ## Example
+/// ``` rust
+/// use fur::display::{DrawBuffer, Rect};
+/// use fur::pixel::PixelFormat;
+/// use fur::style::FontRegistry;
+/// use fur::window::WindowBuilder;
+///
+/// let mut buffer = DrawBuffer::new(200, 100, PixelFormat::Argb);
+/// let mut builder = WindowBuilder::new(200, 100);
+/// builder.title("Hello").title_height(24).border_width(2);
+/// builder.draw(&mut buffer);
+///
+/// assert_eq!(builder.client_rect(), Rect::new(2, 26, 196, 72));
+///
+/// // The title only changes the pixels on the left of the buttons. In narrow windows with tall title bars
+/// // the buttons don't fit, and the last one is cut or disappears, so the title isn't drawn at all.
+/// let fonts = FontRegistry::new();
+/// fonts.load_font(include_bytes!("../../assets/fonts/FurBlocks.ttf"));
+/// for (width, title_height) in [(200, 24), (130, 60), (130, 70)] {
+///     let mut builder = WindowBuilder::new(width, 100);
+///     builder.title_height(title_height).fonts(&fonts);
+///     let mut untitled = DrawBuffer::new(width, 100, PixelFormat::Argb);
+///     builder.draw(&mut untitled);
+///     let mut titled = DrawBuffer::new(width, 100, PixelFormat::Argb);
+///     builder.title("Hello").draw(&mut titled);
+///
+///     let caption = builder.caption_rect();
+///     let pixel_size = PixelFormat::Argb.size();
+///     let pixels = untitled.data().chunks(pixel_size).zip(titled.data().chunks(pixel_size));
+///     let changed = pixels
+///         .enumerate()
+///         .filter(|(_, (old, new))| old != new)
+///         .map(|(i, _)| ((i % width) as isize, (i / width) as isize))
+///         .collect::<Vec<_>>();
+///     assert!(changed.iter().all(|&(x, y)| caption.contains(x, y)));
+///     assert_eq!(changed.is_empty(), caption.is_empty(), "{:?}", caption);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct WindowBuilder {
+    width: usize,
+    height: usize,
+    title_height: usize,
+    border_width: usize,
+    min_width: usize,
+    min_height: usize,
+    title: String,
+    theme: WindowTheme,
+    fonts: FontRegistry,
+}
+
+impl WindowBuilder {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            title_height: 20,
+            border_width: 1,
+            min_width: 0,
+            min_height: 0,
+            title: String::new(),
+            theme: WindowTheme::default(),
+            fonts: FontRegistry::global(),
+        }
+    }
+}
+
+impl WindowBuilder {
+    pub fn width(&mut self, width: usize) -> &mut Self {
+        self.width = width;
+        self
+    }
+
+    pub fn height(&mut self, height: usize) -> &mut Self {
+        self.height = height;
+        self
+    }
+
+    pub fn title_height(&mut self, title_height: usize) -> &mut Self {
+        self.title_height = title_height;
+        self
+    }
+
+    pub fn border_width(&mut self, border_width: usize) -> &mut Self {
+        self.border_width = border_width;
+        self
+    }
+
+    /// Set the minimum width when the window is resized. It is never smaller than the title bar needs.
+    pub fn min_width(&mut self, min_width: usize) -> &mut Self {
+        self.min_width = min_width;
+        self
+    }
+
+    /// Set the minimum height when the window is resized. It is never smaller than the title bar needs.
+    pub fn min_height(&mut self, min_height: usize) -> &mut Self {
+        self.min_height = min_height;
+        self
+    }
+
+    pub fn title<S>(&mut self, title: S) -> &mut Self
+    where
+        String: From<S>,
+    {
+        self.title = String::from(title);
+        self
+    }
+
+    pub fn theme(&mut self, theme: WindowTheme) -> &mut Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Set the fonts used for the title. The global registry is used by default.
+    pub fn fonts(&mut self, fonts: &FontRegistry) -> &mut Self {
+        self.fonts = fonts.clone();
+        self
+    }
+}
+
+impl WindowBuilder {
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// The color of the client area.
+    pub(crate) fn background(&self) -> &Color {
+        &self.theme.background
+    }
+
+    /// The smallest size the window can be resized to, which leaves room for the border, the buttons and a bit of the title.
+    pub fn minimum_size(&self) -> (usize, usize) {
+        let border = self.border_width * 2;
+        (
+            self.min_width.max(border + self.title_height * 4),
+            self.min_height.max(border + self.title_height),
+        )
+    }
+
+    /// The area inside the border and below the title bar, where the content of the window goes.
+    pub fn client_rect(&self) -> Rect {
+        let border = self.border_width;
+        Rect::new(
+            border as isize,
+            (border + self.title_height) as isize,
+            self.width.saturating_sub(border * 2),
+            self.height
+                .saturating_sub(border * 2)
+                .saturating_sub(self.title_height),
+        )
+    }
+
+    /// The title bar inside the border, including the buttons.
+    pub fn title_rect(&self) -> Rect {
+        let border = self.border_width;
+        Rect::new(
+            border as isize,
+            border as isize,
+            self.width.saturating_sub(border * 2),
+            self.title_height
+                .min(self.height.saturating_sub(border * 2)),
+        )
+    }
+
+    /// The part of the title bar on the left of the buttons, where the title is drawn. \
+    /// It is empty if the buttons take the whole title bar.
+    pub fn caption_rect(&self) -> Rect {
+        let title = self.title_rect();
+        Rect::new(
+            title.x,
+            title.y,
+            title.width.saturating_sub(title.height * 3),
+            title.height,
+        )
+    }
+
+    /// The area of a button. Buttons are squares as high as the title bar, from the right: close, maximize, minimize.
+    pub fn button_rect(&self, button: WindowButton) -> Rect {
+        let title = self.title_rect();
+        let slot = match button {
+            WindowButton::Close => 1,
+            WindowButton::Maximize => 2,
+            WindowButton::Minimize => 3,
+        };
+        let size = title.height;
+        Rect::new(title.right() - (size * slot) as isize, title.y, size, size)
+            .intersection(&title)
+            .unwrap_or_default()
+    }
+
+    /// Find the button at a point of the window.
+    pub fn button_at(&self, x: isize, y: isize) -> Option<WindowButton> {
+        [
+            WindowButton::Close,
+            WindowButton::Maximize,
+            WindowButton::Minimize,
+        ]
+        .into_iter()
+        .find(|button| self.button_rect(*button).contains(x, y))
+    }
+
+    /// Find the part of the window at a point of the window. \
+    /// The edges can be grabbed a few pixels inside the border, and the corners a bit further along the edges.
+    pub fn area_at(&self, x: isize, y: isize) -> Option<WindowArea> {
+        const GRIP: usize = 4;
+        if !Rect::new(0, 0, self.width, self.height).contains(x, y) {
+            return None;
+        }
+
+        let grip = self.border_width.max(GRIP) as isize;
+        let corner = grip * 2;
+        let (width, height) = (self.width as isize, self.height as isize);
+        let (left, right) = (x < grip, x >= width - grip);
+        let (top, bottom) = (y < grip, y >= height - grip);
+        let edge = match (
+            top || (y < corner && (left || right)),
+            bottom || (y >= height - corner && (left || right)),
+            left || (x < corner && (top || bottom)),
+            right || (x >= width - corner && (top || bottom)),
+        ) {
+            (true, _, true, _) => Some(ResizeEdge::TopLeft),
+            (true, _, _, true) => Some(ResizeEdge::TopRight),
+            (_, true, true, _) => Some(ResizeEdge::BottomLeft),
+            (_, true, _, true) => Some(ResizeEdge::BottomRight),
+            (true, _, _, _) => Some(ResizeEdge::Top),
+            (_, true, _, _) => Some(ResizeEdge::Bottom),
+            (_, _, true, _) => Some(ResizeEdge::Left),
+            (_, _, _, true) => Some(ResizeEdge::Right),
+            _ => None,
+        };
+
+        if let Some(edge) = edge {
+            Some(WindowArea::Edge(edge))
+        } else if let Some(button) = self.button_at(x, y) {
+            Some(WindowArea::Button(button))
+        } else if self.title_rect().contains(x, y) {
+            Some(WindowArea::TitleBar)
+        } else {
+            Some(WindowArea::Client)
+        }
+    }
+}
+
+impl WindowBuilder {
+    /// Draw the whole window, filling the client area with the background.
+    pub fn draw<T: DisplayDriver>(&self, driver: &mut T) {
+        self.draw_frame(driver);
+        fill_rect(driver, &self.client_rect(), &self.theme.background);
+    }
+
+    /// Draw the border and the title bar only, leaving the client area as it is.
+    pub fn draw_frame<T: DisplayDriver>(&self, driver: &mut T) {
+        let theme = &self.theme;
+        let (width, height, border) = (self.width, self.height, self.border_width);
+        driver.write(0, 0, width, border, &theme.border);
+        driver.write(
+            0,
+            height.saturating_sub(border),
+            width,
+            border,
+            &theme.border,
+        );
+        driver.write(0, 0, border, height, &theme.border);
+        driver.write(
+            width.saturating_sub(border),
+            0,
+            border,
+            height,
+            &theme.border,
+        );
+        fill_rect(driver, &self.title_rect(), &theme.title_bar);
+
+        let caption = self.caption_rect();
+        let padding = caption.height / 4;
+        if !self.title.is_empty() && caption.width > padding * 2 && caption.height > 0 {
+            TextBuilder::new(&self.fonts)
+                .position((caption.x as usize + padding, caption.y as usize))
+                .size((caption.width - padding * 2, caption.height))
+                .font_size(caption.height as f32 * 0.6)
+                .line_height(caption.height as f32)
+                .color(theme.title_text.clone())
+                .text(self.title.as_str())
+                .draw(driver);
+        }
+
+        for button in [
+            WindowButton::Close,
+            WindowButton::Maximize,
+            WindowButton::Minimize,
+        ] {
+            self.draw_button(driver, button);
+        }
+    }
+
+    fn draw_button<T: DisplayDriver>(&self, driver: &mut T, button: WindowButton) {
+        let rect = self.button_rect(button);
+        if rect.width < 6 || rect.height < 6 {
+            return;
+        }
+        if button == WindowButton::Close {
+            fill_rect(driver, &rect, &self.theme.close_button);
+        }
+
+        // The icon is a square in the middle of the button, a third of its size.
+        // A button cut by the left edge of the title bar is narrower than it is high.
+        let size = rect.width.min(rect.height) / 3;
+        let (x, y) = (
+            rect.x as usize + (rect.width - size) / 2,
+            rect.y as usize + (rect.height - size) / 2,
+        );
+        let color = &self.theme.button;
+        match button {
+            WindowButton::Close => {
+                for i in 0..size {
+                    driver.write(x + i, y + i, 1, 1, color);
+                    driver.write(x + size - 1 - i, y + i, 1, 1, color);
+                }
+            }
+            WindowButton::Maximize => {
+                driver.write(x, y, size, 1, color);
+                driver.write(x, y + size - 1, size, 1, color);
+                driver.write(x, y, 1, size, color);
+                driver.write(x + size - 1, y, 1, size, color);
+            }
+            WindowButton::Minimize => driver.write(x, y + size - 1, size, 1, color),
+        }
+    }
+}
+
+fn fill_rect<T: DisplayDriver>(driver: &mut T, rect: &Rect, color: &Color) {
+    if !rect.is_empty() {
+        driver.write(
+            rect.x as usize,
+            rect.y as usize,
+            rect.width,
+            rect.height,
+            color,
+        );
+    }
+}
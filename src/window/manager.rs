@@ -0,0 +1,827 @@
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    string::String,
+};
+
+use crate::{
+    display::{CursorShape, Display, DisplayDriver, Layer, LayerData, Rect},
+    event::{Event, PointerAction, PointerButton, PointerEvent},
+    widget::WidgetTree,
+};
+
+use super::{ResizeEdge, WindowArea, WindowBuilder, WindowButton};
+
+/// The id of a window in a `WindowManager`. \
+/// It stays the same for the whole life of the window and is never used again after the window is destroyed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WindowId {
+    id: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowState {
+    #[default]
+    Normal,
+    /// The window is hidden until it is restored.
+    Minimized,
+    /// The window covers the whole display.
+    Maximized,
+}
+
+/// A window managed by `WindowManager`. It draws into its own layer on the display.
+pub struct Window {
+    layer: Layer,
+    builder: WindowBuilder,
+    state: WindowState,
+    /// The state to go back to when a minimized window is restored.
+    unminimized_state: WindowState,
+    /// The bounds to go back to when a maximized window is restored.
+    restore_bounds: Rect,
+    widgets: WidgetTree,
+}
+
+impl Window {
+    /// The layer of the window, which contains the decorations and the client area.
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// The builder the decorations are drawn with. Use `client_rect` on it to find the client area in the layer.
+    pub fn builder(&self) -> &WindowBuilder {
+        &self.builder
+    }
+
+    pub fn state(&self) -> WindowState {
+        self.state
+    }
+
+    /// The widgets in the client area.
+    pub fn widgets(&self) -> &WidgetTree {
+        &self.widgets
+    }
+}
+
+/// Keeps track of the windows on a display: their layers, their stacking order, their states and the focus. \
+/// Windows stay together in the stacking order, so layers below them (such as a desktop)
+/// and above them (such as a cursor) keep their places.
+/// ## Example
+/// ``` rust
+/// use std::sync::Arc;
+///
+/// use fur::display::{Display, DrawBuffer};
+/// use fur::pixel::PixelFormat;
+/// use fur::window::{WindowBuilder, WindowManager, WindowState};
+/// use spin::RwLock;
+///
+/// let buffer = Arc::new(RwLock::new(DrawBuffer::new(320, 240, PixelFormat::Argb)));
+/// let mut manager = WindowManager::new(Display::new(buffer));
+///
+/// let editor = manager.create_window(WindowBuilder::new(200, 150), 10, 10);
+/// let terminal = manager.create_window(WindowBuilder::new(200, 150), 60, 40);
+/// assert_eq!(manager.focused(), Some(terminal));
+///
+/// manager.minimize(terminal);
+/// assert_eq!(manager.window(terminal).unwrap().state(), WindowState::Minimized);
+/// assert_eq!(manager.focused(), Some(editor));
+///
+/// manager.maximize(editor);
+/// assert_eq!(manager.bounds(editor).unwrap().width, 320);
+/// manager.display_mut().flush();
+/// ```
+pub struct WindowManager {
+    display: Display,
+    windows: BTreeMap<WindowId, Window>,
+    layers: BTreeMap<Layer, WindowId>,
+    next_window_id: usize,
+    focused: Option<WindowId>,
+    /// The window under the pointer.
+    hovered: Option<WindowId>,
+    /// The window which gets all the pointer events while buttons are held down.
+    grab: Option<WindowId>,
+    pressed_buttons: usize,
+    /// Moving, resizing or clicking a button of a window with the left button.
+    drag: Option<Drag>,
+    snap_distance: usize,
+    /// The decoration under the pointer which has changed the shape of the cursor.
+    cursor_area: Option<WindowArea>,
+    events: VecDeque<(WindowId, Event)>,
+}
+
+/// A drag started by pressing the left button on the decorations of a window.
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    window: WindowId,
+    area: WindowArea,
+    /// Where the button was pressed on the display.
+    start: (isize, isize),
+    /// The bounds of the window when the button was pressed.
+    bounds: Rect,
+}
+
+impl WindowManager {
+    pub fn new(display: Display) -> Self {
+        Self {
+            display,
+            windows: BTreeMap::new(),
+            layers: BTreeMap::new(),
+            next_window_id: 0,
+            focused: None,
+            hovered: None,
+            grab: None,
+            pressed_buttons: 0,
+            drag: None,
+            snap_distance: 8,
+            cursor_area: None,
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+
+    /// Get the display, for example to flush it or to manage layers which are not windows. \
+    /// Don't remove or reorder the layers of windows through it.
+    pub fn display_mut(&mut self) -> &mut Display {
+        &mut self.display
+    }
+
+    /// Paint the widgets which have changed into the layers of their windows, and flush the display. \
+    /// Only the areas of these widgets are composited again.
+    pub fn flush(&mut self) {
+        for window in self.windows.values_mut() {
+            if let Some(layer_data) = self.display.layer_mut(&window.layer) {
+                window.widgets.paint(layer_data);
+            }
+        }
+        self.display.flush();
+    }
+}
+
+impl WindowManager {
+    /// Create a window at `(x, y)` with the size of `builder`, on the top of the other windows, and focus it.
+    pub fn create_window(&mut self, builder: WindowBuilder, x: isize, y: isize) -> WindowId {
+        let (width, height) = builder.size();
+        let top = self.top_layer();
+        let layer = self.display.create_layer(width, height, x, y);
+        if let Some(top) = top {
+            self.display.put_upper_than(&layer, &top);
+        }
+        if let Some(layer_data) = self.display.layer_mut(&layer) {
+            builder.draw(layer_data);
+        }
+
+        let id = WindowId {
+            id: self.next_window_id,
+        };
+        self.next_window_id += 1;
+        let mut widgets = WidgetTree::new(builder.client_rect());
+        widgets.set_background(Some(builder.background().clone()));
+        self.windows.insert(
+            id,
+            Window {
+                layer,
+                builder,
+                state: WindowState::Normal,
+                unminimized_state: WindowState::Normal,
+                restore_bounds: Rect::new(x, y, width, height),
+                widgets,
+            },
+        );
+        self.layers.insert(layer, id);
+        self.set_focus(Some(id));
+        id
+    }
+
+    /// Destroy a window and its layer. If it was focused, the top visible window gets the focus.
+    pub fn destroy_window(&mut self, id: WindowId) -> Option<()> {
+        let window = self.windows.remove(&id)?;
+        self.layers.remove(&window.layer);
+        self.display.remove_layer(&window.layer);
+        self.events.retain(|(window, _)| *window != id);
+        if self.hovered == Some(id) {
+            self.hovered = None;
+        }
+        if self.grab == Some(id) {
+            self.grab = None;
+        }
+        if self.focused == Some(id) {
+            self.focus_top();
+        }
+        Some(())
+    }
+
+    pub fn window(&self, id: WindowId) -> Option<&Window> {
+        self.windows.get(&id)
+    }
+
+    /// Get the layer of a window, so that the client area can be drawn.
+    pub fn window_layer_mut(&mut self, id: WindowId) -> Option<&mut LayerData> {
+        let layer = self.windows.get(&id)?.layer;
+        self.display.layer_mut(&layer)
+    }
+
+    /// Get the widgets of a window, to build or change its user interface. \
+    /// The widget tree covers the client area, and is painted on the next `flush`.
+    pub fn widgets_mut(&mut self, id: WindowId) -> Option<&mut WidgetTree> {
+        Some(&mut self.windows.get_mut(&id)?.widgets)
+    }
+
+    /// The area the window covers on the display.
+    pub fn bounds(&self, id: WindowId) -> Option<Rect> {
+        let layer = self.windows.get(&id)?.layer;
+        Some(self.display.layer(&layer)?.bounds())
+    }
+
+    /// Iterate over the windows from the bottom to the top, including minimized ones.
+    pub fn stacking_order(&self) -> impl DoubleEndedIterator<Item = WindowId> + '_ {
+        self.display
+            .paint_order()
+            .filter_map(|layer| self.layers.get(layer).copied())
+    }
+
+    /// Change the title of a window and draw its title bar again.
+    pub fn set_title<S>(&mut self, id: WindowId, title: S) -> Option<()>
+    where
+        String: From<S>,
+    {
+        let window = self.windows.get_mut(&id)?;
+        window.builder.title(title);
+        let layer_data = self.display.layer_mut(&window.layer)?;
+        window.builder.draw_frame(layer_data);
+        Some(())
+    }
+}
+
+/// Only one window has the focus, and it is usually the top one. \
+/// Minimized windows never have the focus.
+impl WindowManager {
+    pub fn focused(&self) -> Option<WindowId> {
+        self.focused
+    }
+
+    /// Raise a window to the top and focus it. A minimized window is restored first.
+    pub fn focus(&mut self, id: WindowId) -> Option<()> {
+        if self.windows.get(&id)?.state == WindowState::Minimized {
+            self.restore(id)?;
+        }
+        self.raise(id)?;
+        self.set_focus(Some(id));
+        Some(())
+    }
+
+    /// Put a window above all the other windows, without changing the focus.
+    pub fn raise(&mut self, id: WindowId) -> Option<()> {
+        let layer = self.windows.get(&id)?.layer;
+        match self.top_layer() {
+            Some(top) => self.display.put_upper_than(&layer, &top),
+            None => Some(()),
+        }
+    }
+
+    /// Put a window below all the other windows. If it was focused, the top visible window gets the focus.
+    pub fn lower(&mut self, id: WindowId) -> Option<()> {
+        let layer = self.windows.get(&id)?.layer;
+        let bottom = self.stacking_order().next();
+        if let Some(bottom) = bottom {
+            let bottom = self.windows.get(&bottom)?.layer;
+            self.display.put_lower_than(&layer, &bottom)?;
+        }
+        if self.focused == Some(id) {
+            self.focus_top();
+        }
+        Some(())
+    }
+
+    /// Give the focus to the top visible window, or to nothing if there is none.
+    fn focus_top(&mut self) {
+        let top = self
+            .stacking_order()
+            .rev()
+            .find(|id| self.windows[id].state != WindowState::Minimized);
+        self.set_focus(top);
+    }
+
+    /// Move the focus, sending `FocusOut` and `FocusIn` to the windows.
+    fn set_focus(&mut self, focused: Option<WindowId>) {
+        if self.focused == focused {
+            return;
+        }
+        if let Some(old) = self.focused.filter(|old| self.windows.contains_key(old)) {
+            self.send(old, Event::FocusOut);
+        }
+        if let Some(new) = focused {
+            self.send(new, Event::FocusIn);
+        }
+        self.focused = focused;
+    }
+
+    /// The layer of the top window.
+    fn top_layer(&self) -> Option<Layer> {
+        let top = self.stacking_order().next_back()?;
+        Some(self.windows[&top].layer)
+    }
+}
+
+impl WindowManager {
+    /// Hide a window. If it was focused, the top visible window gets the focus.
+    pub fn minimize(&mut self, id: WindowId) -> Option<()> {
+        let window = self.windows.get_mut(&id)?;
+        if window.state != WindowState::Minimized {
+            window.unminimized_state = window.state;
+        }
+        window.state = WindowState::Minimized;
+        self.display.layer_mut(&window.layer)?.set_visible(false);
+        if self.focused == Some(id) {
+            self.focus_top();
+        }
+        Some(())
+    }
+
+    /// Make a window cover the whole display, and focus it. \
+    /// The window is drawn again, so the client area has to be drawn again too.
+    pub fn maximize(&mut self, id: WindowId) -> Option<()> {
+        let bounds = self.bounds(id)?;
+        let window = self.windows.get_mut(&id)?;
+        let state = match window.state {
+            WindowState::Minimized => window.unminimized_state,
+            state => state,
+        };
+        if state == WindowState::Normal {
+            window.restore_bounds = bounds;
+        }
+        window.state = WindowState::Maximized;
+        self.display.layer_mut(&window.layer)?.set_visible(true);
+
+        let (width, height) = self.display.size();
+        self.set_bounds(id, Rect::new(0, 0, width, height))?;
+        self.focus(id)
+    }
+
+    /// Show a minimized window in the state it had before, or bring a maximized window back to its normal state,
+    /// and focus it. \
+    /// If a maximized window is restored, it gets its old bounds back and has to be drawn again.
+    pub fn restore(&mut self, id: WindowId) -> Option<()> {
+        let window = self.windows.get_mut(&id)?;
+        let state = core::mem::take(&mut window.state);
+        let restore_bounds = window.restore_bounds;
+        self.display.layer_mut(&window.layer)?.set_visible(true);
+        match state {
+            WindowState::Minimized => {
+                let window = self.windows.get_mut(&id)?;
+                window.state = window.unminimized_state;
+            }
+            WindowState::Maximized => self.set_bounds(id, restore_bounds)?,
+            WindowState::Normal => {}
+        }
+        self.raise(id)?;
+        self.set_focus(Some(id));
+        Some(())
+    }
+
+    /// Move a window, keeping its content.
+    pub fn move_window(&mut self, id: WindowId, x: isize, y: isize) -> Option<()> {
+        let layer = self.windows.get(&id)?.layer;
+        self.display.layer_mut(&layer)?.move_to(x, y);
+        Some(())
+    }
+
+    /// Resize a window. It is drawn again, so the client area has to be drawn again too.
+    pub fn resize_window(&mut self, id: WindowId, width: usize, height: usize) -> Option<()> {
+        let bounds = self.bounds(id)?;
+        self.set_bounds(id, Rect::new(bounds.x, bounds.y, width, height))
+    }
+
+    /// Move and resize the layer of a window. If the size changes, the window is drawn again and gets `Resized`.
+    fn set_bounds(&mut self, id: WindowId, bounds: Rect) -> Option<()> {
+        let window = self.windows.get_mut(&id)?;
+        let layer_data = self.display.layer_mut(&window.layer)?;
+        layer_data.move_to(bounds.x, bounds.y);
+        if layer_data.size() != (bounds.width, bounds.height) {
+            layer_data.resize(bounds.width, bounds.height);
+            window.builder.width(bounds.width).height(bounds.height);
+            window.builder.draw(layer_data);
+            window.widgets.set_bounds(window.builder.client_rect());
+            self.events.push_back((
+                id,
+                Event::Resized {
+                    width: bounds.width,
+                    height: bounds.height,
+                },
+            ));
+        }
+        Some(())
+    }
+}
+
+/// Events are sent to windows through a queue. \
+/// Pointer events go to the window under the pointer, or to the window which got the button down
+/// until all the buttons are released. Keyboard events go to the focused window.
+/// ## Example
+/// ``` rust
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use fur::display::{Display, DisplayDriver, DrawBuffer};
+/// use fur::event::{Event, PointerAction, PointerButton, PointerEvent};
+/// use fur::pixel::PixelFormat;
+/// use fur::widget::{Response, Widget};
+/// use fur::window::{WindowBuilder, WindowManager};
+/// use spin::RwLock;
+///
+/// let buffer = Arc::new(RwLock::new(DrawBuffer::new(320, 240, PixelFormat::Argb)));
+/// let mut manager = WindowManager::new(Display::new(buffer));
+/// let back = manager.create_window(WindowBuilder::new(100, 100), 0, 0);
+/// let front = manager.create_window(WindowBuilder::new(100, 100), 50, 50);
+/// while manager.poll_event().is_some() {}
+///
+/// let down = PointerAction::ButtonDown(PointerButton::Left);
+/// manager.dispatch(Event::Pointer(PointerEvent::new(10, 40, down)));
+/// assert_eq!(manager.poll_event(), Some((back, Event::PointerEnter)));
+/// assert_eq!(manager.poll_event(), Some((front, Event::FocusOut)));
+/// assert_eq!(manager.poll_event(), Some((back, Event::FocusIn)));
+/// assert_eq!(manager.poll_event(), Some((back, Event::Pointer(PointerEvent::new(10, 40, down)))));
+/// assert_eq!(manager.focused(), Some(back));
+///
+/// // The focused widget of a window handles `FocusIn` and `FocusOut`, so they are not queued.
+/// struct Field(Arc<AtomicUsize>);
+///
+/// impl Widget for Field {
+///     fn measure(&self, _children: &[(usize, usize)]) -> (usize, usize) {
+///         (0, 0)
+///     }
+///
+///     fn paint(&mut self, _driver: &mut dyn DisplayDriver) {}
+///
+///     fn handle_event(&mut self, event: &Event) -> Response {
+///         match event {
+///             Event::FocusIn | Event::FocusOut => {
+///                 self.0.fetch_add(1, Ordering::Relaxed);
+///                 Response::Handled
+///             }
+///             _ => Response::Ignored,
+///         }
+///     }
+/// }
+///
+/// let focus_events = Arc::new(AtomicUsize::new(0));
+/// let widgets = manager.widgets_mut(front).unwrap();
+/// let field = widgets.set_root(Field(focus_events.clone()));
+/// widgets.focus(field);
+/// let up = PointerAction::ButtonUp(PointerButton::Left);
+/// for (x, y) in [(10, 40), (120, 120), (10, 40)] {
+///     manager.dispatch(Event::Pointer(PointerEvent::new(x, y, up)));
+///     manager.dispatch(Event::Pointer(PointerEvent::new(x, y, down)));
+///     while let Some(event) = manager.poll_event() {
+///         assert!(!matches!(event, (window, Event::FocusIn | Event::FocusOut) if window == front));
+///     }
+/// }
+/// assert_eq!(focus_events.load(Ordering::Relaxed), 3);
+/// ```
+impl WindowManager {
+    /// Find the top window which is not minimized at a point of the display.
+    pub fn window_at(&self, x: isize, y: isize) -> Option<WindowId> {
+        self.stacking_order().rev().find(|id| {
+            self.windows[id].state != WindowState::Minimized
+                && self
+                    .bounds(*id)
+                    .map_or(false, |bounds| bounds.contains(x, y))
+        })
+    }
+
+    /// Send an event to the right window. \
+    /// Pressing a button on a window focuses it, and moving the pointer between windows sends
+    /// `PointerLeave` and `PointerEnter`. Other events are ignored. \
+    /// Dragging the title bar with the left button moves the window, dragging the edges resizes it,
+    /// and the buttons of the title bar work when they are clicked. The window doesn't get these pointer events.
+    pub fn dispatch(&mut self, event: Event) {
+        match event {
+            Event::Pointer(pointer_event) => self.dispatch_pointer(pointer_event),
+            Event::Key(key_event) => {
+                if let Some(focused) = self.focused {
+                    self.send(focused, Event::Key(key_event));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Take the next event sent to a window. Events handled by the widgets of the window are not returned.
+    pub fn poll_event(&mut self) -> Option<(WindowId, Event)> {
+        self.events.pop_front()
+    }
+
+    /// Offer an input event to the widgets of a window, and queue it for the window if none of them handles it.
+    fn send(&mut self, id: WindowId, event: Event) {
+        let handled = self
+            .windows
+            .get_mut(&id)
+            .and_then(|window| window.widgets.handle_event(&event));
+        if handled.is_none() {
+            self.events.push_back((id, event));
+        }
+    }
+
+    fn dispatch_pointer(&mut self, event: PointerEvent) {
+        self.display.move_cursor(event.x, event.y);
+        let hovered = self.window_at(event.x, event.y);
+        self.update_cursor_shape(hovered, &event);
+        if hovered != self.hovered {
+            if let Some(old) = self.hovered {
+                self.send(old, Event::PointerLeave);
+            }
+            if let Some(new) = hovered {
+                self.send(new, Event::PointerEnter);
+            }
+            self.hovered = hovered;
+        }
+
+        if self.drag.is_some() {
+            self.update_drag(&event);
+            return;
+        }
+
+        let target = self.grab.or(hovered);
+        if let (PointerAction::ButtonDown(PointerButton::Left), None, Some(target)) =
+            (event.action, self.grab, target)
+        {
+            if self.start_drag(target, &event).is_some() {
+                return;
+            }
+        }
+
+        match event.action {
+            PointerAction::ButtonDown(_) => {
+                self.pressed_buttons += 1;
+                if self.grab.is_none() {
+                    self.grab = target;
+                }
+                if let Some(target) = target.filter(|target| self.focused != Some(*target)) {
+                    self.focus(target);
+                }
+            }
+            PointerAction::ButtonUp(_) => {
+                self.pressed_buttons = self.pressed_buttons.saturating_sub(1);
+                if self.pressed_buttons == 0 {
+                    self.grab = None;
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(target) = target {
+            if let Some(bounds) = self.bounds(target) {
+                let event = event.moved_to(event.x - bounds.x, event.y - bounds.y);
+                self.send(target, Event::Pointer(event));
+            }
+        }
+    }
+}
+
+/// Windows are moved and resized by dragging their decorations. \
+/// Their edges snap to the edges of the display when they come within the snap distance. \
+/// A part of the title bar always stays on the display, and windows are not resized beyond the display.
+/// ## Example
+/// ``` rust
+/// use std::sync::Arc;
+///
+/// use fur::display::{Display, DrawBuffer, Rect};
+/// use fur::event::{Event, PointerAction, PointerButton, PointerEvent};
+/// use fur::pixel::PixelFormat;
+/// use fur::window::{WindowBuilder, WindowManager};
+/// use spin::RwLock;
+///
+/// let buffer = Arc::new(RwLock::new(DrawBuffer::new(320, 240, PixelFormat::Argb)));
+/// let mut manager = WindowManager::new(Display::new(buffer));
+/// let window = manager.create_window(WindowBuilder::new(100, 80), 50, 50);
+/// let caption = manager.window(window).unwrap().builder().caption_rect();
+/// let screen = Rect::new(0, 0, 320, 240);
+///
+/// let mut drag = |manager: &mut WindowManager, from: (isize, isize), by: (isize, isize)| {
+///     let (x, y) = (from.0 + by.0, from.1 + by.1);
+///     let left = PointerButton::Left;
+///     manager.dispatch(Event::Pointer(PointerEvent::new(from.0, from.1, PointerAction::ButtonDown(left))));
+///     manager.dispatch(Event::Pointer(PointerEvent::new(x, y, PointerAction::Move)));
+///     manager.dispatch(Event::Pointer(PointerEvent::new(x, y, PointerAction::ButtonUp(left))));
+/// };
+/// for by in [(-5000, 0), (5000, 0), (0, -5000), (0, 5000), (-5000, -5000), (5000, 5000)] {
+///     // Grab the title bar near its left end, and throw the window far past the edges.
+///     let bounds = manager.bounds(window).unwrap();
+///     let grab = (bounds.x + caption.x + 10, bounds.y + caption.y + 5);
+///     drag(&mut manager, grab, by);
+///
+///     let bounds = manager.bounds(window).unwrap();
+///     let on_screen = Rect::new(bounds.x + caption.x, bounds.y + caption.y, caption.width, caption.height)
+///         .intersection(&screen)
+///         .unwrap();
+///     assert!(on_screen.width >= 32 && on_screen.height == caption.height, "{:?}", by);
+///
+///     // It can be brought back by the part of the title bar which is left.
+///     drag(&mut manager, (on_screen.x + 5, on_screen.y + 5), (50 - bounds.x, 50 - bounds.y));
+///     assert_eq!(manager.bounds(window).unwrap(), Rect::new(50, 50, 100, 80));
+/// }
+///
+/// // Resizing from the bottom right corner stops at the size of the display.
+/// drag(&mut manager, (149, 129), (5000, 5000));
+/// assert_eq!(manager.bounds(window).unwrap(), Rect::new(50, 50, 320, 240));
+/// ```
+impl WindowManager {
+    /// How many pixels of the title bar stay on the display at least when a window is dragged.
+    const VISIBLE_TITLE: usize = 32;
+
+    /// Set how close an edge of a window has to be to an edge of the display to snap to it. 0 turns snapping off.
+    pub fn set_snap_distance(&mut self, snap_distance: usize) {
+        self.snap_distance = snap_distance;
+    }
+
+    /// Start a drag if the pointer is on the decorations of the window.
+    fn start_drag(&mut self, id: WindowId, event: &PointerEvent) -> Option<()> {
+        let bounds = self.bounds(id)?;
+        let area = self.windows[&id]
+            .builder
+            .area_at(event.x - bounds.x, event.y - bounds.y)?;
+        if area == WindowArea::Client {
+            return None;
+        }
+
+        self.focus(id);
+        self.drag = Some(Drag {
+            window: id,
+            area,
+            start: (event.x, event.y),
+            bounds,
+        });
+        Some(())
+    }
+
+    fn update_drag(&mut self, event: &PointerEvent) -> Option<()> {
+        let drag = self.drag?;
+        if !self.windows.contains_key(&drag.window) {
+            self.drag = None;
+            return None;
+        }
+
+        match event.action {
+            PointerAction::Move => {
+                if self.windows[&drag.window].state != WindowState::Normal {
+                    return None;
+                }
+                let (dx, dy) = (event.x - drag.start.0, event.y - drag.start.1);
+                match drag.area {
+                    WindowArea::TitleBar => self.drag_move(&drag, dx, dy),
+                    WindowArea::Edge(_) => self.drag_resize(&drag, dx, dy),
+                    _ => None,
+                }
+            }
+            PointerAction::ButtonUp(PointerButton::Left) => {
+                self.drag = None;
+                let bounds = self.bounds(drag.window)?;
+                let area = self.windows[&drag.window]
+                    .builder
+                    .area_at(event.x - bounds.x, event.y - bounds.y);
+                match drag.area {
+                    WindowArea::Button(button) if area == Some(drag.area) => {
+                        self.click_button(drag.window, button)
+                    }
+                    _ => Some(()),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn drag_move(&mut self, drag: &Drag, dx: isize, dy: isize) -> Option<()> {
+        let (width, height) = self.display.size();
+        let snap = |position: isize, size: usize, limit: usize| {
+            let far = limit as isize - size as isize;
+            if position.unsigned_abs() <= self.snap_distance {
+                0
+            } else if (position - far).unsigned_abs() <= self.snap_distance {
+                far
+            } else {
+                position
+            }
+        };
+        let x = snap(drag.bounds.x + dx, drag.bounds.width, width);
+        let y = snap(drag.bounds.y + dy, drag.bounds.height, height);
+
+        // Keep a part of the title bar on the display, so that the window can be dragged back.
+        let builder = &self.windows[&drag.window].builder;
+        let caption = match builder.caption_rect() {
+            caption if caption.is_empty() => builder.title_rect(),
+            caption => caption,
+        };
+        let visible = caption.width.min(Self::VISIBLE_TITLE) as isize;
+        let x = x
+            .min(width as isize - visible - caption.x)
+            .max(visible - caption.right());
+        let y = y.min(height as isize - caption.bottom()).max(-caption.y);
+        self.move_window(drag.window, x, y)
+    }
+
+    fn drag_resize(&mut self, drag: &Drag, dx: isize, dy: isize) -> Option<()> {
+        let WindowArea::Edge(edge) = drag.area else {
+            return None;
+        };
+        let (top, bottom, left, right) = edge.sides();
+        let (min_width, min_height) = self.windows[&drag.window].builder.minimum_size();
+        let (width, height) = self.display.size();
+        let (width, height) = (width as isize, height as isize);
+        let snap = |position: isize, limit: isize| {
+            if position.unsigned_abs() <= self.snap_distance {
+                0
+            } else if (position - limit).unsigned_abs() <= self.snap_distance {
+                limit
+            } else {
+                position
+            }
+        };
+
+        // The window does not grow beyond the display, unless its minimum size does.
+        let bounds = drag.bounds;
+        let (mut x0, mut y0, mut x1, mut y1) =
+            (bounds.x, bounds.y, bounds.right(), bounds.bottom());
+        if left {
+            x0 = snap(x0 + dx, width)
+                .max(x1 - width)
+                .min(x1 - min_width as isize);
+        }
+        if right {
+            x1 = snap(x1 + dx, width)
+                .min(x0 + width)
+                .max(x0 + min_width as isize);
+        }
+        if top {
+            y0 = snap(y0 + dy, height)
+                .max(y1 - height)
+                .min(y1 - min_height as isize);
+        }
+        if bottom {
+            y1 = snap(y1 + dy, height)
+                .min(y0 + height)
+                .max(y0 + min_height as isize);
+        }
+
+        let bounds = Rect::new(x0, y0, (x1 - x0) as usize, (y1 - y0) as usize);
+        if Some(bounds) != self.bounds(drag.window) {
+            self.set_bounds(drag.window, bounds)?;
+        }
+        Some(())
+    }
+
+    /// Show a resize cursor on the edges of windows, and the arrow on the rest of the decorations. \
+    /// It only happens if the display has a built-in cursor, and the cursor in the client area is left to the window.
+    fn update_cursor_shape(&mut self, hovered: Option<WindowId>, event: &PointerEvent) {
+        if self.display.cursor_shape().is_none() {
+            return;
+        }
+
+        let area = match (self.drag, hovered) {
+            (Some(drag), _) => Some(drag.area),
+            (None, Some(id)) => self.bounds(id).and_then(|bounds| {
+                self.windows[&id]
+                    .builder
+                    .area_at(event.x - bounds.x, event.y - bounds.y)
+            }),
+            (None, None) => None,
+        }
+        .filter(|area| *area != WindowArea::Client);
+        if area == self.cursor_area {
+            return;
+        }
+
+        let shape = match area {
+            Some(WindowArea::Edge(ResizeEdge::Top | ResizeEdge::Bottom)) => {
+                CursorShape::ResizeVertical
+            }
+            Some(WindowArea::Edge(ResizeEdge::Left | ResizeEdge::Right)) => {
+                CursorShape::ResizeHorizontal
+            }
+            Some(WindowArea::Edge(ResizeEdge::TopLeft | ResizeEdge::BottomRight)) => {
+                CursorShape::ResizeDiagonal
+            }
+            Some(WindowArea::Edge(ResizeEdge::TopRight | ResizeEdge::BottomLeft)) => {
+                CursorShape::ResizeAntiDiagonal
+            }
+            _ => CursorShape::Arrow,
+        };
+        self.display.set_cursor_shape(shape);
+        self.cursor_area = area;
+    }
+
+    fn click_button(&mut self, id: WindowId, button: WindowButton) -> Option<()> {
+        match button {
+            WindowButton::Close => {
+                self.events.push_back((id, Event::CloseRequested));
+                Some(())
+            }
+            WindowButton::Maximize if self.windows.get(&id)?.state == WindowState::Maximized => {
+                self.restore(id)
+            }
+            WindowButton::Maximize => self.maximize(id),
+            WindowButton::Minimize => self.minimize(id),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod builder;
+mod manager;
+
+pub use builder::*;
+pub use manager::*;